@@ -0,0 +1,33 @@
+-- 試合結果テーブル（再戦した場合は同じmatching_idで試合ごとに記録される）
+CREATE TABLE IF NOT EXISTS matches (
+    id TEXT PRIMARY KEY,
+    matching_id TEXT NOT NULL,
+
+    -- 試合結果
+    outcome TEXT NOT NULL,       -- 'Win', 'Draw'
+    winner_id TEXT,              -- 引き分け・チーム戦の場合はNULL
+    winning_team TEXT,           -- 'A', 'B'（チーム戦以外・引き分けの場合はNULL）
+    end_reason TEXT NOT NULL,    -- 'KnockOut', 'TimeUp', 'Surrender', 'Disconnect'
+    rounds INTEGER NOT NULL,
+    play_time_seconds INTEGER NOT NULL,
+
+    -- メタデータ
+    started_at TEXT NOT NULL,
+    finished_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_matches_matching_id ON matches(matching_id);
+
+-- 試合の参加者テーブル
+CREATE TABLE IF NOT EXISTS match_players (
+    match_id TEXT NOT NULL REFERENCES matches(id) ON DELETE CASCADE,
+    player_id TEXT NOT NULL,
+    join_order INTEGER NOT NULL, -- 参加順（0始まり）
+    monster_id TEXT NOT NULL,    -- 選択したモンスターID
+    team TEXT,                   -- 'A', 'B'（チーム戦以外はNULL）
+    placement INTEGER NOT NULL,  -- 最終順位（1位が1）
+    score INTEGER NOT NULL,      -- ラウンド勝利数
+    PRIMARY KEY (match_id, player_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_match_players_player_id ON match_players(player_id);
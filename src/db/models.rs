@@ -1,6 +1,8 @@
-use chrono::Utc;
+use crate::models::{GameResult, Team};
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::SqlitePool;
+use std::collections::HashMap;
 
 /// データベースモンスター情報
 #[derive(Debug, Clone, sqlx::FromRow, Serialize, Deserialize)]
@@ -188,3 +190,254 @@ impl Monster {
         Ok(monsters)
     }
 }
+
+/// 試合結果の記録
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MatchRecord {
+    pub id: String, // 試合のgame_id（リプレイのIDと同じ）
+    pub matching_id: String,
+
+    // 試合結果
+    pub outcome: String, // DBには文字列として保存（'Win', 'Draw'）
+    pub winner_id: Option<String>,
+    pub winning_team: Option<String>,
+    pub end_reason: String,
+    pub rounds: i64,
+    pub play_time_seconds: i64,
+
+    // メタデータ
+    pub started_at: String,
+    pub finished_at: String,
+
+    /// 参加者（参加順）
+    pub players: Vec<MatchPlayerRecord>,
+}
+
+/// 試合の参加者ごとの記録
+#[derive(Debug, Clone, sqlx::FromRow, Serialize, Deserialize)]
+pub struct MatchPlayerRecord {
+    pub player_id: String,
+    pub monster_id: String,   // 選択したモンスターID
+    pub team: Option<String>, // チーム戦以外はNone
+    pub placement: i64,       // 最終順位（1位が1）
+    pub score: i64,           // ラウンド勝利数
+}
+
+impl MatchRecord {
+    /// 試合結果から記録を作成
+    /// `monster_ids`は各プレイヤーが選択したモンスターID、`teams`はチーム戦の所属
+    pub fn from_result(
+        result: &GameResult,
+        monster_ids: &HashMap<String, String>,
+        teams: &HashMap<String, Team>,
+        rounds: u32,
+        started_at: DateTime<Utc>,
+    ) -> Self {
+        let players = result
+            .player_ids
+            .iter()
+            .map(|player_id| MatchPlayerRecord {
+                player_id: player_id.clone(),
+                monster_id: monster_ids.get(player_id).cloned().unwrap_or_default(),
+                team: teams.get(player_id).map(|team| format!("{:?}", team)),
+                placement: result
+                    .placements
+                    .iter()
+                    .position(|id| id == player_id)
+                    .map_or(result.player_ids.len(), |index| index + 1)
+                    as i64,
+                score: result.scores.get(player_id).copied().unwrap_or(0) as i64,
+            })
+            .collect();
+
+        Self {
+            id: result.game_id.to_string(),
+            matching_id: result.matching_id.to_string(),
+            outcome: format!("{:?}", result.outcome),
+            winner_id: result.winner_id.clone(),
+            winning_team: result.winning_team.map(|team| format!("{:?}", team)),
+            end_reason: format!("{:?}", result.end_reason),
+            rounds: rounds as i64,
+            play_time_seconds: result.play_time_seconds,
+            started_at: started_at.to_rfc3339(),
+            finished_at: result.finished_at.to_rfc3339(),
+            players,
+        }
+    }
+
+    /// 試合結果と参加者をデータベースに挿入
+    pub async fn insert(&self, pool: &SqlitePool) -> Result<(), sqlx::Error> {
+        let mut tx = pool.begin().await?;
+
+        sqlx::query!(
+            r#"
+            INSERT INTO matches (
+                id, matching_id, outcome, winner_id, winning_team, end_reason,
+                rounds, play_time_seconds, started_at, finished_at
+            )
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+            self.id,
+            self.matching_id,
+            self.outcome,
+            self.winner_id,
+            self.winning_team,
+            self.end_reason,
+            self.rounds,
+            self.play_time_seconds,
+            self.started_at,
+            self.finished_at
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        for (join_order, player) in self.players.iter().enumerate() {
+            let join_order = join_order as i64;
+            sqlx::query!(
+                r#"
+                INSERT INTO match_players (
+                    match_id, player_id, join_order, monster_id, team, placement, score
+                )
+                VALUES (?, ?, ?, ?, ?, ?, ?)
+                "#,
+                self.id,
+                player.player_id,
+                join_order,
+                player.monster_id,
+                player.team,
+                player.placement,
+                player.score
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    /// IDで試合結果を取得
+    #[allow(dead_code)]
+    pub async fn find_by_id(
+        pool: &SqlitePool,
+        id: &str,
+    ) -> Result<Option<MatchRecord>, sqlx::Error> {
+        let Some(row) = sqlx::query!(
+            r#"
+            SELECT
+                id as "id!",
+                matching_id as "matching_id!",
+                outcome as "outcome!",
+                winner_id,
+                winning_team,
+                end_reason as "end_reason!",
+                rounds as "rounds!",
+                play_time_seconds as "play_time_seconds!",
+                started_at as "started_at!",
+                finished_at as "finished_at!"
+            FROM matches WHERE id = ?
+            "#,
+            id
+        )
+        .fetch_optional(pool)
+        .await?
+        else {
+            return Ok(None);
+        };
+
+        let players = Self::list_players(pool, &row.id).await?;
+        Ok(Some(MatchRecord {
+            id: row.id,
+            matching_id: row.matching_id,
+            outcome: row.outcome,
+            winner_id: row.winner_id,
+            winning_team: row.winning_team,
+            end_reason: row.end_reason,
+            rounds: row.rounds,
+            play_time_seconds: row.play_time_seconds,
+            started_at: row.started_at,
+            finished_at: row.finished_at,
+            players,
+        }))
+    }
+
+    /// プレイヤーが参加した試合結果を新しい順に取得
+    #[allow(dead_code)]
+    pub async fn list_by_player(
+        pool: &SqlitePool,
+        player_id: &str,
+        limit: i64,
+    ) -> Result<Vec<MatchRecord>, sqlx::Error> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT
+                m.id as "id!",
+                m.matching_id as "matching_id!",
+                m.outcome as "outcome!",
+                m.winner_id,
+                m.winning_team,
+                m.end_reason as "end_reason!",
+                m.rounds as "rounds!",
+                m.play_time_seconds as "play_time_seconds!",
+                m.started_at as "started_at!",
+                m.finished_at as "finished_at!"
+            FROM matches m
+            JOIN match_players p ON p.match_id = m.id
+            WHERE p.player_id = ?
+            ORDER BY m.finished_at DESC
+            LIMIT ?
+            "#,
+            player_id,
+            limit
+        )
+        .fetch_all(pool)
+        .await?;
+
+        let mut records = Vec::with_capacity(rows.len());
+        for row in rows {
+            let players = Self::list_players(pool, &row.id).await?;
+            records.push(MatchRecord {
+                id: row.id,
+                matching_id: row.matching_id,
+                outcome: row.outcome,
+                winner_id: row.winner_id,
+                winning_team: row.winning_team,
+                end_reason: row.end_reason,
+                rounds: row.rounds,
+                play_time_seconds: row.play_time_seconds,
+                started_at: row.started_at,
+                finished_at: row.finished_at,
+                players,
+            });
+        }
+
+        Ok(records)
+    }
+
+    /// 試合の参加者を参加順に取得
+    async fn list_players(
+        pool: &SqlitePool,
+        match_id: &str,
+    ) -> Result<Vec<MatchPlayerRecord>, sqlx::Error> {
+        let players = sqlx::query_as!(
+            MatchPlayerRecord,
+            r#"
+            SELECT
+                player_id as "player_id!",
+                monster_id as "monster_id!",
+                team,
+                placement as "placement!",
+                score as "score!"
+            FROM match_players
+            WHERE match_id = ?
+            ORDER BY join_order
+            "#,
+            match_id
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(players)
+    }
+}
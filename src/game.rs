@@ -1,2 +1,8 @@
 pub mod state;
 pub mod manager;
+pub mod combat;
+pub mod snapshot;
+pub mod projectile;
+pub mod arena;
+pub mod bot;
+pub mod replay;
@@ -0,0 +1,200 @@
+use crate::geometry::{Aabb, Capsule, Ray, Shape};
+use crate::models::{MAX_PLAYERS, Vector3};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// アリーナ定義ファイルの配置ディレクトリ
+pub const ARENA_DATA_DIR: &str = "data/arenas";
+
+/// アリーナの移動可能範囲（軸平行な直方体）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArenaBounds {
+    pub min: Vector3,
+    pub max: Vector3,
+}
+
+impl ArenaBounds {
+    /// 座標が範囲内か
+    pub fn contains(&self, position: &Vector3) -> bool {
+        (self.min.x..=self.max.x).contains(&position.x)
+            && (self.min.y..=self.max.y).contains(&position.y)
+            && (self.min.z..=self.max.z).contains(&position.z)
+    }
+
+    /// 座標を範囲内にクランプ
+    pub fn clamp(&self, position: &Vector3) -> Vector3 {
+        Vector3 {
+            x: position.x.clamp(self.min.x, self.max.x),
+            y: position.y.clamp(self.min.y, self.max.y),
+            z: position.z.clamp(self.min.z, self.max.z),
+        }
+    }
+}
+
+/// キャラクターの初期配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpawnPoint {
+    pub position: Vector3,
+    pub rotation: Vector3,
+}
+
+/// アリーナ上の静的な障害物
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "shape", rename_all = "snake_case")]
+pub enum Obstacle {
+    /// 軸平行な直方体（壁・箱など）
+    Box { min: Vector3, max: Vector3 },
+    /// 鉛直な円柱（柱・木など）。`base` は底面の中心
+    Cylinder {
+        base: Vector3,
+        radius: f32,
+        height: f32,
+    },
+}
+
+impl Obstacle {
+    /// 衝突判定用の形状（円柱は高さ方向のカプセルとして扱う）
+    pub fn shape(&self) -> Shape {
+        match self {
+            Obstacle::Box { min, max } => Shape::Aabb(Aabb {
+                min: min.clone(),
+                max: max.clone(),
+            }),
+            Obstacle::Cylinder {
+                base,
+                radius,
+                height,
+            } => Shape::Capsule(Capsule {
+                start: base.clone(),
+                end: Vector3 {
+                    y: base.y + height,
+                    ..base.clone()
+                },
+                radius: *radius,
+            }),
+        }
+    }
+}
+
+/// アリーナ（ステージ）定義
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Arena {
+    pub id: String,
+    pub name: String,
+    pub bounds: ArenaBounds,
+    /// 初期配置（プレイヤーの参加順に割り当てる）
+    pub spawn_points: Vec<SpawnPoint>,
+    #[serde(default)]
+    pub obstacles: Vec<Obstacle>,
+}
+
+impl Arena {
+    /// 定義の妥当性を検証
+    pub fn validate(&self) -> Result<(), String> {
+        let ArenaBounds { min, max } = &self.bounds;
+        if min.x >= max.x || min.y > max.y || min.z >= max.z {
+            return Err(format!("arena {}: bounds min must be below max", self.id));
+        }
+        // 最大人数の試合でも全員が別々の初期配置に置かれるようにする
+        if self.spawn_points.len() < MAX_PLAYERS {
+            return Err(format!(
+                "arena {}: at least {} spawn points are required",
+                self.id, MAX_PLAYERS
+            ));
+        }
+        if let Some(index) = self
+            .spawn_points
+            .iter()
+            .position(|spawn| !self.bounds.contains(&spawn.position))
+        {
+            return Err(format!(
+                "arena {}: spawn point {} is outside the bounds",
+                self.id, index
+            ));
+        }
+        let invalid_obstacle = self.obstacles.iter().position(|obstacle| match obstacle {
+            Obstacle::Box { min, max } => min.x > max.x || min.y > max.y || min.z > max.z,
+            Obstacle::Cylinder { radius, height, .. } => *radius <= 0.0 || *height <= 0.0,
+        });
+        if let Some(index) = invalid_obstacle {
+            return Err(format!(
+                "arena {}: obstacle {} has invalid dimensions",
+                self.id, index
+            ));
+        }
+        Ok(())
+    }
+
+    /// 2点間の直線経路が障害物に遮られていないか
+    pub fn is_path_clear(&self, from: &Vector3, to: &Vector3) -> bool {
+        let ray = Ray::between(from, to);
+        !self
+            .obstacles
+            .iter()
+            .any(|obstacle| obstacle.shape().intersects(&ray))
+    }
+
+    /// 参加人数分の初期配置があるか
+    pub fn has_spawn_points_for(&self, player_count: usize) -> bool {
+        self.spawn_points.len() >= player_count
+    }
+
+    /// 参加順に対応する初期配置（足りない場合は先頭から繰り返す）
+    pub fn spawn_point(&self, index: usize) -> &SpawnPoint {
+        &self.spawn_points[index % self.spawn_points.len()]
+    }
+}
+
+/// 読み込み済みのアリーナ一覧
+#[derive(Debug, Clone, Default)]
+pub struct ArenaRegistry {
+    arenas: HashMap<String, Arena>,
+}
+
+impl ArenaRegistry {
+    /// アリーナを登録（不正な定義やIDの重複はエラー）
+    pub fn insert(&mut self, arena: Arena) -> Result<(), String> {
+        arena.validate()?;
+        if self.arenas.contains_key(&arena.id) {
+            return Err(format!("duplicate arena id: {}", arena.id));
+        }
+        self.arenas.insert(arena.id.clone(), arena);
+        Ok(())
+    }
+
+    /// ディレクトリ内の `*.json` からアリーナ定義を読み込む
+    pub fn load_dir(dir: impl AsRef<Path>) -> Result<Self, String> {
+        let dir = dir.as_ref();
+        let entries = std::fs::read_dir(dir)
+            .map_err(|e| format!("failed to read {}: {}", dir.display(), e))?;
+
+        let mut paths: Vec<_> = entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+            .collect();
+        paths.sort();
+
+        let mut registry = Self::default();
+        for path in paths {
+            let json = std::fs::read_to_string(&path)
+                .map_err(|e| format!("failed to read {}: {}", path.display(), e))?;
+            let arena: Arena = serde_json::from_str(&json)
+                .map_err(|e| format!("failed to parse {}: {}", path.display(), e))?;
+            registry.insert(arena)?;
+        }
+        Ok(registry)
+    }
+
+    /// IDからアリーナを取得
+    pub fn get(&self, arena_id: &str) -> Option<&Arena> {
+        self.arenas.get(arena_id)
+    }
+
+    /// 登録済みのアリーナID一覧（ソート済み）
+    pub fn ids(&self) -> Vec<&str> {
+        let mut ids: Vec<&str> = self.arenas.keys().map(String::as_str).collect();
+        ids.sort();
+        ids
+    }
+}
@@ -0,0 +1,398 @@
+use crate::game::manager::{ApplyDamage, GameManager, ProcessInput, ProcessStateUpdate};
+use crate::handlers::MatchingSessions;
+use crate::models::{
+    AttackType, BotDifficulty, Character, InputAction, PlayerInput, Vector3, WsMessage,
+};
+use crate::utils::{
+    add_vector3, multiply_vector3, normalize_vector3, subtract_vector3, vector3_length,
+};
+use actix::prelude::*;
+use chrono::{DateTime, Utc};
+use std::time::Duration;
+use tokio::sync::mpsc;
+use uuid::Uuid;
+
+/// 遠距離型のボットが保とうとする相手との距離
+const RANGED_PREFERRED_DISTANCE: f32 = 8.0;
+/// 目標距離からこの範囲内なら移動しない
+const DISTANCE_TOLERANCE: f32 = 0.5;
+/// 近距離攻撃を狙う距離（attack_rangeに加える値、相手のヒットボックス分）
+const MELEE_REACH_SLACK: f32 = 1.0;
+
+/// 難易度ごとの行動パラメータ
+#[derive(Debug, Clone)]
+pub struct BotProfile {
+    /// 思考間隔（ms）。短いほど反応が速い
+    pub think_interval_ms: u64,
+    /// 攻撃方向のぶれの最大値（度）
+    pub aim_error_deg: f32,
+    /// move_speedに対する移動速度の割合
+    pub speed_ratio: f32,
+    /// 攻撃可能な時に実際に攻撃する確率
+    pub attack_chance: f32,
+}
+
+impl BotProfile {
+    pub fn for_difficulty(difficulty: BotDifficulty) -> Self {
+        match difficulty {
+            BotDifficulty::Easy => Self {
+                think_interval_ms: 500,
+                aim_error_deg: 25.0,
+                speed_ratio: 0.6,
+                attack_chance: 0.4,
+            },
+            BotDifficulty::Normal => Self {
+                think_interval_ms: 250,
+                aim_error_deg: 10.0,
+                speed_ratio: 0.8,
+                attack_chance: 0.7,
+            },
+            BotDifficulty::Hard => Self {
+                think_interval_ms: 100,
+                aim_error_deg: 0.0,
+                speed_ratio: 1.0,
+                attack_chance: 1.0,
+            },
+        }
+    }
+}
+
+/// ボットの行動（GameManagerへの入力に変換して送信する）
+#[derive(Debug, Clone, PartialEq)]
+pub enum BotAction {
+    /// 移動・回転（StateUpdateとして送信）
+    Move {
+        position: Vector3,
+        rotation: Vector3,
+    },
+    /// 攻撃（Inputとして送信）
+    Attack {
+        attack_type: AttackType,
+        position: Vector3,
+        direction: Vector3,
+    },
+}
+
+/// ボットの思考
+/// 受信したメッセージから戦況を把握し、思考間隔ごとに行動を決める
+#[derive(Debug, Clone)]
+pub struct BotBrain {
+    pub player_id: String,
+    pub profile: BotProfile,
+    me: Option<Character>,
+    opponent: Option<Character>,
+    paused: bool,
+    finished: bool,
+    last_attack_at: Option<DateTime<Utc>>,
+    /// サーバーが命中と判定した、未報告の被弾（攻撃種別）
+    pending_hits: Vec<AttackType>,
+}
+
+impl BotBrain {
+    pub fn new(player_id: String, difficulty: BotDifficulty) -> Self {
+        Self {
+            player_id,
+            profile: BotProfile::for_difficulty(difficulty),
+            me: None,
+            opponent: None,
+            paused: false,
+            finished: false,
+            last_attack_at: None,
+            pending_hits: Vec::new(),
+        }
+    }
+
+    /// 試合が終了したか
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    /// 受信したメッセージを戦況に反映
+    pub fn observe(&mut self, msg: &WsMessage) {
+        match msg {
+            WsMessage::GameStart {
+                your_character,
+                opponent_character,
+                ..
+            }
+            | WsMessage::RoundStart {
+                your_character,
+                opponent_character,
+                ..
+            }
+            | WsMessage::GameResync {
+                your_character,
+                opponent_character,
+                ..
+            } => {
+                self.me = Some(your_character.clone());
+                self.opponent = Some(opponent_character.clone());
+                self.last_attack_at = None;
+                self.pending_hits.clear();
+            }
+            WsMessage::OpponentStateUpdate { opponent, .. } => {
+                self.opponent = Some(opponent.clone());
+            }
+            WsMessage::GameSnapshot { characters, .. } => {
+                for delta in characters {
+                    let target = if delta.player_id == self.player_id {
+                        self.me.as_mut()
+                    } else {
+                        self.opponent.as_mut()
+                    };
+                    let Some(character) = target else {
+                        continue;
+                    };
+                    if let Some(position) = &delta.position {
+                        character.position = position.clone();
+                    }
+                    if let Some(rotation) = &delta.rotation {
+                        character.rotation = rotation.clone();
+                    }
+                    if let Some(hp) = delta.hp {
+                        character.hp = hp;
+                    }
+                }
+            }
+            WsMessage::PositionCorrection { position, .. } => {
+                if let Some(me) = self.me.as_mut() {
+                    me.position = position.clone();
+                }
+            }
+            WsMessage::HpUpdate { player_id, hp, .. } => {
+                let target = if *player_id == self.player_id {
+                    self.me.as_mut()
+                } else {
+                    self.opponent.as_mut()
+                };
+                if let Some(character) = target {
+                    character.hp = *hp;
+                }
+            }
+            WsMessage::AttackResult {
+                defender_id,
+                attack_type,
+                hit: true,
+                ..
+            } if *defender_id == self.player_id => {
+                // 人間のクライアントと同じく、命中確定した攻撃を被弾報告する
+                self.pending_hits.push(attack_type.clone());
+            }
+            WsMessage::OpponentDisconnected { .. } => self.paused = true,
+            WsMessage::OpponentReconnected { .. } => self.paused = false,
+            WsMessage::GameEnd { .. } => self.finished = true,
+            _ => {}
+        }
+    }
+
+    /// 未報告の被弾を取り出す（ApplyDamageとして送信する）
+    pub fn take_damage_reports(&mut self) -> Vec<AttackType> {
+        std::mem::take(&mut self.pending_hits)
+    }
+
+    /// 次の行動を決める
+    pub fn decide(&mut self, now: DateTime<Utc>) -> Vec<BotAction> {
+        self.decide_with_roll(now, rand::random::<f32>(), rand::random::<f32>())
+    }
+
+    /// 乱数値(0.0〜1.0)を指定して次の行動を決める
+    /// `aim_roll` は攻撃方向のぶれ、`attack_roll` は攻撃するかの判定に使う
+    pub fn decide_with_roll(
+        &mut self,
+        now: DateTime<Utc>,
+        aim_roll: f32,
+        attack_roll: f32,
+    ) -> Vec<BotAction> {
+        if self.paused || self.finished {
+            return Vec::new();
+        }
+        let (Some(me), Some(opponent)) = (self.me.as_mut(), self.opponent.as_ref()) else {
+            return Vec::new();
+        };
+        if !me.is_alive() || !opponent.is_alive() {
+            return Vec::new();
+        }
+
+        // 水平面上の相手への向きと距離
+        let mut to_opponent = subtract_vector3(&opponent.position, &me.position);
+        to_opponent.y = 0.0;
+        let distance = vector3_length(&to_opponent);
+        let facing = normalize_vector3(&to_opponent);
+        let rotation = Vector3::new(0.0, facing.x.atan2(facing.z).to_degrees(), 0.0);
+
+        // 近距離型は射程まで近づき、遠距離型は距離を保つ
+        let stats = &me.stats;
+        let prefers_melee = stats.short_range_attack_power >= stats.long_range_attack_power;
+        let melee_reach = stats.attack_range as f32 + MELEE_REACH_SLACK;
+        let preferred_distance = if prefers_melee {
+            (stats.attack_range as f32 * 0.8).max(DISTANCE_TOLERANCE)
+        } else {
+            RANGED_PREFERRED_DISTANCE
+        };
+        let max_step = stats.move_speed as f32
+            * self.profile.speed_ratio
+            * self.profile.think_interval_ms as f32
+            / 1000.0;
+        let step = if distance > preferred_distance + DISTANCE_TOLERANCE {
+            (distance - preferred_distance).min(max_step)
+        } else if distance < preferred_distance - DISTANCE_TOLERANCE {
+            -max_step
+        } else {
+            0.0
+        };
+        me.position = add_vector3(&me.position, &multiply_vector3(&facing, step));
+        me.rotation = rotation.clone();
+
+        let mut actions = vec![BotAction::Move {
+            position: me.position.clone(),
+            rotation,
+        }];
+
+        // クールダウンが明けていれば、確率で攻撃（向きは難易度に応じてぶれる）
+        let cooldown_ready = self.last_attack_at.is_none_or(|last| {
+            now.signed_duration_since(last).num_milliseconds() >= stats.attack_cooldown
+        });
+        let attack_type = if distance <= melee_reach && stats.short_range_attack_power > 0 {
+            Some(AttackType::Normal)
+        } else if stats.long_range_attack_power > 0 {
+            Some(AttackType::Special)
+        } else {
+            None
+        };
+        if let Some(attack_type) = attack_type.filter(|_| {
+            cooldown_ready && distance > 0.0 && attack_roll < self.profile.attack_chance
+        }) {
+            let error = (aim_roll * 2.0 - 1.0) * self.profile.aim_error_deg.to_radians();
+            let (sin, cos) = error.sin_cos();
+            let direction = Vector3::new(
+                facing.x * cos + facing.z * sin,
+                0.0,
+                facing.z * cos - facing.x * sin,
+            );
+            actions.push(BotAction::Attack {
+                attack_type,
+                position: me.position.clone(),
+                direction,
+            });
+            self.last_attack_at = Some(now);
+        }
+
+        actions
+    }
+}
+
+/// サーバー内で動作するボットプレイヤー
+/// 人間のプレイヤーと同じくWsMessageを受信し、GameManagerへ入力を送信する
+pub struct BotPlayer {
+    matching_id: Uuid,
+    brain: BotBrain,
+    rx: mpsc::UnboundedReceiver<WsMessage>,
+    game_manager: Addr<GameManager>,
+    sessions: MatchingSessions,
+}
+
+impl BotPlayer {
+    pub fn new(
+        matching_id: Uuid,
+        brain: BotBrain,
+        rx: mpsc::UnboundedReceiver<WsMessage>,
+        game_manager: Addr<GameManager>,
+        sessions: MatchingSessions,
+    ) -> Self {
+        Self {
+            matching_id,
+            brain,
+            rx,
+            game_manager,
+            sessions,
+        }
+    }
+
+    /// 受信メッセージを処理して行動を送信
+    fn think(&mut self, ctx: &mut Context<Self>) {
+        loop {
+            match self.rx.try_recv() {
+                Ok(msg) => self.brain.observe(&msg),
+                Err(mpsc::error::TryRecvError::Empty) => break,
+                Err(mpsc::error::TryRecvError::Disconnected) => {
+                    ctx.stop();
+                    return;
+                }
+            }
+        }
+
+        // 試合終了またはマッチングが削除されたら停止
+        let session_exists = self
+            .sessions
+            .lock()
+            .map(|sessions| sessions.contains_key(&self.matching_id))
+            .unwrap_or(false);
+        if self.brain.is_finished() || !session_exists {
+            ctx.stop();
+            return;
+        }
+
+        for attack_type in self.brain.take_damage_reports() {
+            self.game_manager.do_send(ApplyDamage {
+                matching_id: self.matching_id,
+                player_id: self.brain.player_id.clone(),
+                attack_type,
+            });
+        }
+
+        let now = Utc::now();
+        for action in self.brain.decide(now) {
+            match action {
+                BotAction::Move { position, rotation } => {
+                    self.game_manager.do_send(ProcessStateUpdate {
+                        matching_id: self.matching_id,
+                        player_id: self.brain.player_id.clone(),
+                        position,
+                        rotation,
+                        seq: None,
+                    });
+                }
+                BotAction::Attack {
+                    attack_type,
+                    position,
+                    direction,
+                } => {
+                    self.game_manager.do_send(ProcessInput {
+                        matching_id: self.matching_id,
+                        input: PlayerInput {
+                            player_id: self.brain.player_id.clone(),
+                            action: InputAction::Attack {
+                                attack_type,
+                                position,
+                                direction,
+                                view_time: None,
+                            },
+                            seq: None,
+                            timestamp: now,
+                        },
+                    });
+                }
+            }
+        }
+    }
+}
+
+impl Actor for BotPlayer {
+    type Context = Context<Self>;
+
+    fn started(&mut self, ctx: &mut Self::Context) {
+        println!(
+            "🤖 Bot started: matching_id={}, player_id={}",
+            self.matching_id, self.brain.player_id
+        );
+        let interval = Duration::from_millis(self.brain.profile.think_interval_ms);
+        ctx.run_interval(interval, |act, ctx| act.think(ctx));
+    }
+
+    fn stopped(&mut self, _ctx: &mut Self::Context) {
+        println!(
+            "🤖 Bot stopped: matching_id={}, player_id={}",
+            self.matching_id, self.brain.player_id
+        );
+    }
+}
@@ -0,0 +1,67 @@
+use crate::geometry::closest_point_on_segment;
+use crate::models::{AttackType, Vector3};
+use crate::utils::{distance, dot_vector3, normalize_vector3, subtract_vector3, vector3_length};
+
+/// 近距離攻撃（Normal）の攻撃判定の半角（度）
+const NORMAL_ATTACK_HALF_ANGLE_DEG: f32 = 60.0;
+/// 遠距離攻撃（Special）の攻撃判定の半角（度）
+const SPECIAL_ATTACK_HALF_ANGLE_DEG: f32 = 10.0;
+
+/// 攻撃が命中するかを判定
+///
+/// 攻撃者の位置から `direction` 方向の扇形（攻撃種別ごとの角度、半径 `attack_range`）が
+/// 防御側のヒットボックス（半径 `hitbox_radius` の球）に触れていれば命中とする。
+pub fn is_attack_hit(
+    attack_type: &AttackType,
+    attacker_position: &Vector3,
+    direction: &Vector3,
+    attack_range: f32,
+    defender_position: &Vector3,
+    hitbox_radius: f32,
+) -> bool {
+    let to_defender = subtract_vector3(defender_position, attacker_position);
+    let distance = vector3_length(&to_defender);
+
+    // 射程外
+    if distance > attack_range + hitbox_radius {
+        return false;
+    }
+    // ヒットボックスに重なっている場合は向きに関係なく命中
+    if distance <= hitbox_radius {
+        return true;
+    }
+
+    let direction = normalize_vector3(direction);
+    if vector3_length(&direction) == 0.0 {
+        return false; // 向きが不正
+    }
+
+    let half_angle = match attack_type {
+        AttackType::Normal => NORMAL_ATTACK_HALF_ANGLE_DEG,
+        AttackType::Special => SPECIAL_ATTACK_HALF_ANGLE_DEG,
+    }
+    .to_radians();
+
+    // ヒットボックスの見かけの大きさ分だけ判定角度を広げる
+    let angle = (dot_vector3(&direction, &to_defender) / distance)
+        .clamp(-1.0, 1.0)
+        .acos();
+    let hitbox_angle = (hitbox_radius / distance).clamp(-1.0, 1.0).asin();
+
+    angle <= half_angle + hitbox_angle
+}
+
+/// 弾が移動区間内で防御側のヒットボックスに触れたかを判定
+///
+/// 1ティックで大きく移動した場合のすり抜けを防ぐため、移動前後を結ぶ線分と
+/// ヒットボックス中心との最短距離で判定する。
+pub fn is_projectile_hit(
+    start: &Vector3,
+    end: &Vector3,
+    projectile_radius: f32,
+    defender_position: &Vector3,
+    hitbox_radius: f32,
+) -> bool {
+    let closest = closest_point_on_segment(start, end, defender_position);
+    distance(&closest, defender_position) <= projectile_radius + hitbox_radius
+}
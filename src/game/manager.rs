@@ -1,8 +1,17 @@
-use crate::game::state::GameStateManager;
-use crate::handlers::MatchingSessions;
-use crate::models::{GameResult, WsMessage};
+use crate::db::models::MatchRecord;
+use crate::game::projectile::{Projectile, ProjectileEvent};
+use crate::game::replay::ReplayRecorder;
+use crate::game::state::{
+    AttackJudgement, DamageResult, GameStateManager, MoveValidation, RoundProgress, RoundResult,
+};
+use crate::handlers::{MatchingSessions, Replays};
+use crate::models::{
+    AttackType, Character, EndReason, GameResult, MAX_SPECTATORS_PER_MATCH, MatchingStatus,
+    REMATCH_WINDOW_SECONDS, ReplayEventKind, WsMessage,
+};
 use actix::prelude::*;
-use chrono::Utc;
+use chrono::{DateTime, Utc};
+use sqlx::SqlitePool;
 use std::collections::HashMap;
 use std::time::Duration;
 use tokio::sync::mpsc;
@@ -16,8 +25,16 @@ pub struct GameManager {
     games: HashMap<Uuid, GameStateManager>,
     /// WebSocket送信用チャンネル (matching_id -> (player_id -> sender))
     ws_senders: HashMap<Uuid, HashMap<String, mpsc::UnboundedSender<WsMessage>>>,
+    /// 観戦者の送信用チャンネル (matching_id -> (観戦者のセッションID -> sender))
+    spectators: HashMap<Uuid, HashMap<Uuid, mpsc::UnboundedSender<WsMessage>>>,
     /// 共有マッチングセッション
     sessions: MatchingSessions,
+    /// リプレイの保存先（Noneの場合は記録しない）
+    replays: Option<Replays>,
+    /// 記録中のリプレイ (matching_id -> 記録)
+    recorders: HashMap<Uuid, ReplayRecorder>,
+    /// 試合結果の保存先（Noneの場合は保存しない）
+    db_pool: Option<SqlitePool>,
 }
 
 impl GameManager {
@@ -25,40 +42,52 @@ impl GameManager {
         Self {
             games: HashMap::new(),
             ws_senders: HashMap::new(),
+            spectators: HashMap::new(),
             sessions,
+            replays: None,
+            recorders: HashMap::new(),
+            db_pool: None,
         }
     }
 
-    /// 特定のプレイヤーが更新した時、相手にのみ状態を送信
-    fn send_opponent_state_for_player(&self, matching_id: &Uuid, player_id: &str) {
-        if let Some(game) = self.games.get(matching_id) {
-            if let Some(senders) = self.ws_senders.get(matching_id) {
-                let now = Utc::now();
-
-                if player_id == &game.player_a_id {
-                    // Aが更新 → Bに通知
-                    if let Some(sender_b) = senders.get(&game.player_b_id) {
-                        let msg = WsMessage::OpponentStateUpdate {
-                            opponent: game.player_a_character.clone(),
-                            timestamp: now,
-                        };
-                        let _ = sender_b.send(msg);
-                    }
-                } else if player_id == &game.player_b_id {
-                    // Bが更新 → Aに通知
-                    if let Some(sender_a) = senders.get(&game.player_a_id) {
-                        let msg = WsMessage::OpponentStateUpdate {
-                            opponent: game.player_b_character.clone(),
-                            timestamp: now,
-                        };
-                        let _ = sender_a.send(msg);
-                    }
-                }
-            }
+    /// 試合のリプレイを記録し、終了時に保存する
+    pub fn with_replays(mut self, replays: Replays) -> Self {
+        self.replays = Some(replays);
+        self
+    }
+
+    /// 試合終了時に試合結果をデータベースに保存する
+    pub fn with_db(mut self, db_pool: SqlitePool) -> Self {
+        self.db_pool = Some(db_pool);
+        self
+    }
+
+    /// リプレイにイベントを記録（記録していないゲームは無視）
+    fn record_replay(&mut self, matching_id: &Uuid, now: DateTime<Utc>, event: ReplayEventKind) {
+        let tick = self.games.get(matching_id).map_or(0, |game| game.tick);
+        if let Some(recorder) = self.recorders.get_mut(matching_id) {
+            recorder.record(tick, now, event);
         }
     }
 
-    /// 特定のプレイヤーが攻撃した時、相手にのみ攻撃情報を送信
+    /// 特定のプレイヤーが更新した時、他の参加者にのみ状態を送信
+    fn send_opponent_state_for_player(&self, matching_id: &Uuid, player_id: &str) {
+        let Some(character) = self
+            .games
+            .get(matching_id)
+            .and_then(|game| game.character(player_id))
+        else {
+            return;
+        };
+        let msg = WsMessage::OpponentStateUpdate {
+            player_id: player_id.to_string(),
+            opponent: character.clone(),
+            timestamp: Utc::now(),
+        };
+        self.send_to_others(matching_id, player_id, msg);
+    }
+
+    /// 特定のプレイヤーが攻撃した時、他の参加者にのみ攻撃情報を送信
     fn send_opponent_attack(
         &self,
         matching_id: &Uuid,
@@ -67,44 +96,469 @@ impl GameManager {
         position: crate::models::Vector3,
         direction: crate::models::Vector3,
     ) {
-        if let Some(game) = self.games.get(matching_id) {
-            if let Some(senders) = self.ws_senders.get(matching_id) {
-                let now = Utc::now();
+        let msg = WsMessage::OpponentAttacked {
+            attacker_id: attacker_id.to_string(),
+            attack_type,
+            position,
+            direction,
+            timestamp: Utc::now(),
+        };
+        self.send_to_others(matching_id, attacker_id, msg);
+    }
 
-                let opponent_id = if attacker_id == game.player_a_id {
-                    &game.player_b_id
-                } else {
-                    &game.player_a_id
-                };
+    /// 攻撃の命中判定結果を参加者全員に送信
+    fn broadcast_attack_result(
+        &mut self,
+        matching_id: &Uuid,
+        attacker_id: &str,
+        attack_type: crate::models::AttackType,
+        judgement: AttackJudgement,
+    ) {
+        let now = Utc::now();
+        self.record_replay(
+            matching_id,
+            now,
+            ReplayEventKind::Attack {
+                attacker_id: attacker_id.to_string(),
+                defender_id: judgement.defender_id.clone(),
+                attack_type: attack_type.clone(),
+                hit: judgement.hit,
+                blocked: judgement.blocked,
+            },
+        );
+        let msg = WsMessage::AttackResult {
+            attacker_id: attacker_id.to_string(),
+            defender_id: judgement.defender_id,
+            attack_type,
+            hit: judgement.hit,
+            blocked: judgement.blocked,
+            timestamp: now,
+        };
+        self.broadcast(matching_id, msg);
+    }
 
-                if let Some(sender) = senders.get(opponent_id) {
-                    let msg = WsMessage::OpponentAttacked {
-                        attacker_id: attacker_id.to_string(),
-                        attack_type,
-                        position,
-                        direction,
+    /// 弾の発射を参加者全員に送信
+    fn broadcast_projectile_spawned(&self, matching_id: &Uuid, projectile: &Projectile) {
+        let msg = WsMessage::ProjectileSpawned {
+            projectile_id: projectile.id,
+            owner_id: projectile.owner_id.clone(),
+            position: projectile.position.clone(),
+            velocity: projectile.velocity.clone(),
+            expires_at: projectile.expires_at,
+            timestamp: projectile.spawned_at,
+        };
+        self.broadcast(matching_id, msg);
+    }
+
+    /// 飛行中の弾を進め、命中・寿命切れを参加者全員に送信
+    fn update_projectiles(&mut self, matching_id: &Uuid, now: DateTime<Utc>) {
+        let Some(game) = self
+            .games
+            .get_mut(matching_id)
+            .filter(|game| !game.is_paused())
+        else {
+            return;
+        };
+
+        for event in game.update_projectiles(now) {
+            match event {
+                ProjectileEvent::Hit {
+                    projectile,
+                    target_id,
+                    damage,
+                } => {
+                    println!(
+                        "💥 Projectile hit: matching_id={}, owner={}, target={}, damage={}",
+                        matching_id, projectile.owner_id, target_id, damage.damage
+                    );
+                    let msg = WsMessage::ProjectileHit {
+                        projectile_id: projectile.id,
+                        owner_id: projectile.owner_id,
+                        target_id: target_id.clone(),
+                        position: projectile.position,
                         timestamp: now,
                     };
-                    let _ = sender.send(msg);
+                    self.broadcast(matching_id, msg);
+                    self.broadcast_hp_update(matching_id, &target_id, &damage);
+                }
+                ProjectileEvent::Blocked { projectile } => {
+                    self.broadcast_projectile_expired(matching_id, &projectile, true, now);
+                }
+                ProjectileEvent::Expired { projectile } => {
+                    self.broadcast_projectile_expired(matching_id, &projectile, false, now);
                 }
             }
         }
     }
 
-    /// ゲーム終了通知を送信
-    fn broadcast_game_end(&mut self, matching_id: &Uuid, result: GameResult) {
+    /// 弾の消滅（障害物への衝突・寿命切れ）を参加者全員に送信
+    fn broadcast_projectile_expired(
+        &self,
+        matching_id: &Uuid,
+        projectile: &Projectile,
+        blocked: bool,
+        now: DateTime<Utc>,
+    ) {
+        let msg = WsMessage::ProjectileExpired {
+            projectile_id: projectile.id,
+            position: projectile.position.clone(),
+            blocked,
+            timestamp: now,
+        };
+        self.broadcast(matching_id, msg);
+    }
+
+    /// 特定のプレイヤーにのみメッセージを送信
+    fn send_to_player(&self, matching_id: &Uuid, player_id: &str, msg: WsMessage) {
+        if let Some(sender) = self
+            .ws_senders
+            .get(matching_id)
+            .and_then(|senders| senders.get(player_id))
+        {
+            let _ = sender.send(msg);
+        }
+    }
+
+    /// 指定したプレイヤー以外の参加者と観戦者にメッセージを送信
+    fn send_to_others(&self, matching_id: &Uuid, player_id: &str, msg: WsMessage) {
+        if let Some(senders) = self.ws_senders.get(matching_id) {
+            for (_, sender) in senders.iter().filter(|(id, _)| *id != player_id) {
+                let _ = sender.send(msg.clone());
+            }
+        }
+        self.send_to_spectators(matching_id, msg);
+    }
+
+    /// 参加者全員と観戦者にメッセージを送信
+    fn broadcast(&self, matching_id: &Uuid, msg: WsMessage) {
         if let Some(senders) = self.ws_senders.get(matching_id) {
-            let msg = WsMessage::GameEnd {
-                result,
-                timestamp: Utc::now(),
-            };
             for sender in senders.values() {
                 let _ = sender.send(msg.clone());
             }
         }
-        // 終了したゲームを削除
+        self.send_to_spectators(matching_id, msg);
+    }
+
+    /// 観戦者にのみメッセージを送信
+    fn send_to_spectators(&self, matching_id: &Uuid, msg: WsMessage) {
+        if let Some(spectators) = self.spectators.get(matching_id) {
+            for sender in spectators.values() {
+                let _ = sender.send(msg.clone());
+            }
+        }
+    }
+
+    /// 観戦者向けに試合全体の現在の状態を生成（ゲーム中でなければNone）
+    fn spectator_sync(&self, matching_id: &Uuid, now: DateTime<Utc>) -> Option<WsMessage> {
+        let game = self.games.get(matching_id)?;
+        Some(WsMessage::SpectatorSync {
+            matching_id: *matching_id,
+            player_ids: game.player_ids.clone(),
+            characters: game.characters.clone(),
+            teams: game.teams.clone(),
+            round: game.current_round,
+            rounds: game.rounds,
+            scores: game.scores.clone(),
+            time_limit_seconds: game.time_limit_seconds,
+            remaining_seconds: game.remaining_seconds(now),
+            paused: game.is_paused(),
+            arena_id: game.arena.as_ref().map(|arena| arena.id.clone()),
+            timestamp: now,
+        })
+    }
+
+    /// 降参・切断によるプレイヤーの脱落を参加者全員に送信（試合は続行）
+    fn broadcast_player_eliminated(
+        &self,
+        matching_id: &Uuid,
+        player_id: &str,
+        end_reason: EndReason,
+        now: DateTime<Utc>,
+    ) {
+        println!(
+            "☠️ Player eliminated: matching_id={}, player_id={}, reason={:?}",
+            matching_id, player_id, end_reason
+        );
+        let msg = WsMessage::PlayerEliminated {
+            player_id: player_id.to_string(),
+            end_reason,
+            timestamp: now,
+        };
+        self.broadcast(matching_id, msg);
+    }
+
+    /// HP更新をゲーム参加者全員に送信
+    fn broadcast_hp_update(&mut self, matching_id: &Uuid, player_id: &str, damage: &DamageResult) {
+        let Some((hp, max_hp)) = self
+            .games
+            .get(matching_id)
+            .and_then(|game| game.character(player_id))
+            .map(|character| (character.hp, character.max_hp))
+        else {
+            return;
+        };
+        let now = Utc::now();
+        self.record_replay(
+            matching_id,
+            now,
+            ReplayEventKind::Damage {
+                player_id: player_id.to_string(),
+                hp,
+                max_hp,
+                damage: damage.damage,
+                is_critical: damage.is_critical,
+            },
+        );
+        let msg = WsMessage::HpUpdate {
+            player_id: player_id.to_string(),
+            hp,
+            max_hp,
+            damage: damage.damage,
+            is_critical: damage.is_critical,
+            timestamp: now,
+        };
+        self.broadcast(matching_id, msg);
+    }
+
+    /// ゲームのティックを進め、送信タイミングであればスナップショットを各プレイヤーに送信
+    /// 各プレイヤーには最後にACKしたスナップショットからの差分のみを送る
+    fn advance_tick(&mut self, matching_id: &Uuid, now: DateTime<Utc>) {
+        let Some(game) = self.games.get_mut(matching_id) else {
+            return;
+        };
+        let tick = game.advance_tick();
+        let paused = game.is_paused();
+        let last_processed_seqs: HashMap<String, Option<u32>> = game
+            .player_ids
+            .iter()
+            .map(|player_id| (player_id.clone(), game.last_processed_seq(player_id)))
+            .collect();
+        let Some(tracker) = game
+            .snapshots
+            .as_mut()
+            .filter(|t| t.is_due(tick) && !paused)
+        else {
+            return;
+        };
+
+        let characters: Vec<(&str, &Character)> = game
+            .player_ids
+            .iter()
+            .filter_map(|id| game.characters.get(id).map(|c| (id.as_str(), c)))
+            .collect();
+        let Some(senders) = self.ws_senders.get(matching_id) else {
+            return;
+        };
+        for (player_id, sender) in senders {
+            let (baseline_tick, deltas) = tracker.delta_for(player_id, &characters);
+            let msg = WsMessage::GameSnapshot {
+                tick,
+                baseline_tick,
+                characters: deltas,
+                last_processed_seq: last_processed_seqs.get(player_id).copied().flatten(),
+                timestamp: now,
+            };
+            let _ = sender.send(msg);
+        }
+        tracker.record(tick, &characters);
+    }
+
+    /// 切断中のプレイヤーの再接続猶予を他の参加者に通知
+    fn send_disconnect_countdown(&self, matching_id: &Uuid, now: DateTime<Utc>) {
+        if let Some(game) = self.games.get(matching_id) {
+            for (player_id, deadline) in &game.disconnect_deadlines {
+                let msg = WsMessage::OpponentDisconnected {
+                    player_id: player_id.clone(),
+                    remaining_seconds: deadline.signed_duration_since(now).num_seconds().max(0),
+                    reconnect_deadline: *deadline,
+                    timestamp: now,
+                };
+                self.send_to_others(matching_id, player_id, msg);
+            }
+        }
+    }
+
+    /// 現在のラウンド開始を各プレイヤーに通知（キャラクターは自分/相手の視点で送信）
+    fn send_round_start(&mut self, matching_id: &Uuid) {
+        let now = Utc::now();
+        if let Some(game) = self.games.get(matching_id) {
+            for (player_id, your_character) in game.players() {
+                let Some(opponent_character) = game.first_opponent_character(player_id) else {
+                    continue;
+                };
+                let msg = WsMessage::RoundStart {
+                    round: game.current_round,
+                    your_character: your_character.clone(),
+                    opponent_character: opponent_character.clone(),
+                    opponent_characters: game.opponent_characters(player_id),
+                    scores: game.scores.clone(),
+                    timestamp: now,
+                };
+                self.send_to_player(matching_id, player_id, msg);
+            }
+        }
+
+        // 観戦者には全員分のキャラクターをまとめて送信
+        if let Some(sync) = self.spectator_sync(matching_id, now) {
+            self.send_to_spectators(matching_id, sync);
+        }
+
+        // リプレイにはラウンド開始時の全員のキャラクターを記録
+        if let Some(event) = self
+            .games
+            .get(matching_id)
+            .map(|game| ReplayEventKind::RoundStart {
+                round: game.current_round,
+                characters: game.characters.clone(),
+            })
+        {
+            self.record_replay(matching_id, now, event);
+        }
+    }
+
+    /// ラウンドを終了し、スコアを通知して次のラウンドまたは試合終了へ進める
+    fn finish_round(&mut self, matching_id: &Uuid, round: RoundResult, now: DateTime<Utc>) {
+        let Some(game) = self.games.get_mut(matching_id) else {
+            return;
+        };
+        let progress = game.conclude_round(&round, now);
+        let scores = game.scores.clone();
+
+        println!(
+            "🔔 Round {} over: matching_id={}, winner={:?}, team={:?}, reason={:?}, scores={:?}",
+            round.round, matching_id, round.winner_id, round.winning_team, round.end_reason, scores
+        );
+
+        self.record_replay(
+            matching_id,
+            now,
+            ReplayEventKind::RoundEnd {
+                round: round.round,
+                winner_id: round.winner_id.clone(),
+                winning_team: round.winning_team,
+                end_reason: round.end_reason.clone(),
+                scores: scores.clone(),
+            },
+        );
+        let msg = WsMessage::RoundEnd {
+            round: round.round,
+            winner_id: round.winner_id,
+            winning_team: round.winning_team,
+            end_reason: round.end_reason,
+            scores,
+            timestamp: now,
+        };
+        self.broadcast(matching_id, msg);
+
+        match progress {
+            RoundProgress::NextRound => self.send_round_start(matching_id),
+            RoundProgress::MatchOver(result) => self.finish_game(matching_id, result),
+        }
+    }
+
+    /// ゲームを終了し、結果通知とセッションのバトル終了フラグ更新を行う
+    fn finish_game(&mut self, matching_id: &Uuid, result: GameResult) {
+        println!(
+            "🏁 Game over: matching_id={}, outcome={:?}, winner={:?}, team={:?}, reason={:?}, play_time={}s",
+            matching_id,
+            result.outcome,
+            result.winner_id,
+            result.winning_team,
+            result.end_reason,
+            result.play_time_seconds
+        );
+
+        // セッションのバトル終了フラグを更新し、再戦の受付を開始
+        let rematch_deadline = Utc::now() + chrono::Duration::seconds(REMATCH_WINDOW_SECONDS);
+        if let Ok(mut sessions) = self.sessions.lock() {
+            if let Some(session) = sessions.get_mut(matching_id) {
+                session.status = MatchingStatus::Finished;
+                session.is_battle_finished = true;
+                session.rematch_deadline = Some(rematch_deadline);
+                session.rematch_requested_by = None;
+                println!("🏁 Battle finished for matching: {}", matching_id);
+            }
+        }
+
+        self.save_match_record(matching_id, &result);
+        self.save_replay(matching_id, &result);
+        self.broadcast_game_end(matching_id, result, rematch_deadline);
+    }
+
+    /// 試合結果（参加者・選択したモンスター・勝者・試合時間）を非同期でデータベースに保存
+    fn save_match_record(&self, matching_id: &Uuid, result: &GameResult) {
+        let (Some(db_pool), Some(game)) = (self.db_pool.clone(), self.games.get(matching_id))
+        else {
+            return;
+        };
+        let monster_ids: HashMap<String, String> = game
+            .characters
+            .iter()
+            .map(|(player_id, character)| (player_id.clone(), character.model_id.clone()))
+            .collect();
+        let record = MatchRecord::from_result(
+            result,
+            &monster_ids,
+            &game.teams,
+            game.rounds,
+            game.actual_started_at,
+        );
+        actix::spawn(async move {
+            match record.insert(&db_pool).await {
+                Ok(()) => println!(
+                    "🗄️  Match result saved: id={}, matching_id={}",
+                    record.id, record.matching_id
+                ),
+                Err(e) => println!(
+                    "❌ Failed to save match result: matching_id={}, {}",
+                    record.matching_id, e
+                ),
+            }
+        });
+    }
+
+    /// 記録中のリプレイを試合結果付きで終了し、非同期でファイルに保存
+    fn save_replay(&mut self, matching_id: &Uuid, result: &GameResult) {
+        let (Some(recorder), Some(replays)) =
+            (self.recorders.remove(matching_id), self.replays.clone())
+        else {
+            return;
+        };
+        let replay = recorder.finish(result.clone(), Utc::now());
+        actix::spawn(async move {
+            match replays.save(&replay).await {
+                Ok(path) => println!(
+                    "🎞️ Replay saved: matching_id={}, game_id={}, events={}, path={}",
+                    replay.matching_id,
+                    replay.game_id,
+                    replay.events.len(),
+                    path.display()
+                ),
+                Err(e) => println!(
+                    "❌ Failed to save replay: matching_id={}, {}",
+                    replay.matching_id, e
+                ),
+            }
+        });
+    }
+
+    /// ゲーム終了通知を送信
+    fn broadcast_game_end(
+        &mut self,
+        matching_id: &Uuid,
+        result: GameResult,
+        rematch_deadline: DateTime<Utc>,
+    ) {
+        let msg = WsMessage::GameEnd {
+            result,
+            rematch_deadline,
+            timestamp: Utc::now(),
+        };
+        self.broadcast(matching_id, msg);
+        // 終了したゲームを削除（観戦者の送信チャンネルも解放する）
         self.games.remove(matching_id);
         self.ws_senders.remove(matching_id);
+        self.spectators.remove(matching_id);
     }
 }
 
@@ -112,44 +566,65 @@ impl Actor for GameManager {
     type Context = Context<Self>;
 
     fn started(&mut self, ctx: &mut Self::Context) {
-        // 60Hz更新ループを開始（勝敗判定のみ）
+        // 60Hz更新ループを開始（スナップショット配信・弾の移動と命中判定・切断による不戦敗・ラウンドの勝敗判定）
         ctx.run_interval(Duration::from_millis(TICK_INTERVAL_MS), |act, _ctx| {
             let game_ids: Vec<Uuid> = act.games.keys().cloned().collect();
+            let now = Utc::now();
 
             for matching_id in game_ids {
-                if let Some(game) = act.games.get(&matching_id) {
-                    // 勝者判定
-                    if game.is_game_over() {
-                        if let Some(winner_id) = game.check_winner() {
-                            let loser_id = if winner_id == game.player_a_id {
-                                game.player_b_id.clone()
-                            } else {
-                                game.player_a_id.clone()
-                            };
-
-                            let result = GameResult {
-                                matching_id,
-                                winner_id,
-                                loser_id,
-                                player_a_id: game.player_a_id.clone(),
-                                player_b_id: game.player_b_id.clone(),
-                                play_time_seconds: 0, // TODO: 実際のプレイ時間を計算
-                                finished_at: Utc::now(),
-                            };
-
-                            act.broadcast_game_end(&matching_id, result);
-
-                            // セッションのバトル終了フラグを更新
-                            if let Ok(mut sessions) = act.sessions.lock() {
-                                if let Some(session) = sessions.get_mut(&matching_id) {
-                                    session.is_battle_finished = true;
-                                    println!("🏁 Battle finished for matching: {}", matching_id);
-                                }
-                            }
-                        }
-                    }
-                    // 状態送信は削除（更新時のみ送信するように変更）
+                // スナップショット配信（有効な場合のみ）
+                act.advance_tick(&matching_id, now);
+
+                // 弾の移動と命中判定（一時停止中は止める）
+                act.update_projectiles(&matching_id, now);
+
+                // 再接続期限切れによる不戦敗判定（残りが2人以上なら脱落のみ通知して続行）
+                let Some((forfeited, result)) = act.games.get_mut(&matching_id).map(|game| {
+                    let forfeited_before = game.forfeited.len();
+                    let result = game.judge_disconnect(now);
+                    (game.forfeited[forfeited_before..].to_vec(), result)
+                }) else {
+                    continue;
+                };
+                if let Some(result) = result {
+                    act.finish_game(&matching_id, result);
+                    continue;
                 }
+                for player_id in forfeited {
+                    act.broadcast_player_eliminated(
+                        &matching_id,
+                        &player_id,
+                        EndReason::Disconnect,
+                        now,
+                    );
+                }
+
+                // ラウンドの勝敗判定（KOまたは時間切れ、一時停止中は判定しない）
+                let round = act
+                    .games
+                    .get(&matching_id)
+                    .filter(|game| !game.is_paused())
+                    .and_then(|game| game.judge_round(now));
+                if let Some(round) = round {
+                    act.finish_round(&matching_id, round, now);
+                }
+                // 通常の状態送信は更新時のみ（スナップショットはオプション）
+            }
+        });
+
+        // 1秒ごとに残り時間を通知（一時停止中は再接続の猶予をカウントダウン）
+        ctx.run_interval(Duration::from_secs(1), |act, _ctx| {
+            let now = Utc::now();
+            for (matching_id, game) in act.games.iter() {
+                if game.is_paused() {
+                    act.send_disconnect_countdown(matching_id, now);
+                    continue;
+                }
+                let msg = WsMessage::TimeUpdate {
+                    remaining_seconds: game.remaining_seconds(now),
+                    timestamp: now,
+                };
+                act.broadcast(matching_id, msg);
             }
         });
 
@@ -176,6 +651,8 @@ impl Actor for GameManager {
                     // ゲームマネージャーからも削除
                     act.games.remove(&id);
                     act.ws_senders.remove(&id);
+                    act.spectators.remove(&id);
+                    act.recorders.remove(&id);
                 }
             }
         });
@@ -196,30 +673,42 @@ impl Handler<StartGame> for GameManager {
     fn handle(&mut self, msg: StartGame, _ctx: &mut Self::Context) {
         let matching_id = msg.game.matching_id;
 
-        // 各プレイヤーに相手のキャラクター情報のみを送信
+        // 各プレイヤーに自分と相手のキャラクター情報を送信
         let now = Utc::now();
 
-        if let Some(sender_a) = msg.ws_senders.get(&msg.game.player_a_id) {
+        for (player_id, your_character) in msg.game.players() {
+            let (Some(sender), Some(opponent_character)) = (
+                msg.ws_senders.get(player_id),
+                msg.game.first_opponent_character(player_id),
+            ) else {
+                continue;
+            };
             let start_msg = WsMessage::GameStart {
-                opponent_character: msg.game.player_b_character.clone(),
-                your_player_id: msg.game.player_a_id.clone(),
+                your_character: your_character.clone(),
+                opponent_character: opponent_character.clone(),
+                opponent_characters: msg.game.opponent_characters(player_id),
+                teams: msg.game.teams.clone(),
+                your_player_id: player_id.to_string(),
+                time_limit_seconds: msg.game.time_limit_seconds,
+                rounds: msg.game.rounds,
+                arena_id: msg.game.arena.as_ref().map(|arena| arena.id.clone()),
                 timestamp: now,
             };
-            let _ = sender_a.send(start_msg);
+            let _ = sender.send(start_msg);
         }
 
-        if let Some(sender_b) = msg.ws_senders.get(&msg.game.player_b_id) {
-            let start_msg = WsMessage::GameStart {
-                opponent_character: msg.game.player_a_character.clone(),
-                your_player_id: msg.game.player_b_id.clone(),
-                timestamp: now,
-            };
-            let _ = sender_b.send(start_msg);
+        // リプレイの記録を開始
+        if self.replays.is_some() {
+            self.recorders
+                .insert(matching_id, ReplayRecorder::new(&msg.game, now));
         }
 
         // ゲームを登録
         self.games.insert(matching_id, msg.game);
         self.ws_senders.insert(matching_id, msg.ws_senders);
+
+        // 1ラウンド目の開始を通知
+        self.send_round_start(&matching_id);
     }
 }
 
@@ -235,11 +724,53 @@ impl Handler<ProcessInput> for GameManager {
     type Result = ();
 
     fn handle(&mut self, msg: ProcessInput, _ctx: &mut Self::Context) {
-        if let Some(game) = self.games.get_mut(&msg.matching_id) {
+        if let Some(game) = self
+            .games
+            .get_mut(&msg.matching_id)
+            .filter(|game| !game.is_paused())
+        {
             let player_id = msg.input.player_id.clone();
             let action = msg.input.action.clone(); // 先にアクションをクローン
 
-            game.process_input(msg.input); // ここで msg.input の所有権が移動
+            // 脱落したプレイヤーの入力は破棄
+            if !game.can_act(&player_id) {
+                println!(
+                    "⚠️ Dropped input from eliminated player: player_id={}",
+                    player_id
+                );
+                return;
+            }
+
+            // 重複・順序逆転した入力は破棄
+            if !game.accept_sequence(&player_id, msg.input.seq) {
+                println!(
+                    "⚠️ Dropped out-of-order input: player_id={}, seq={:?}, last_processed_seq={:?}",
+                    player_id,
+                    msg.input.seq,
+                    game.last_processed_seq(&player_id)
+                );
+                return;
+            }
+
+            // ここで msg.input の所有権が移動
+            if let Err(message) = game.process_input(msg.input) {
+                // クールダウン中の攻撃は相手に中継せず、送信者にのみエラーを返す
+                println!("❌ Input rejected: player_id={}, {}", player_id, message);
+                self.send_to_player(&msg.matching_id, &player_id, WsMessage::Error { message });
+                return;
+            }
+
+            // 受理した入力をリプレイに記録
+            if let Some(recorder) = self.recorders.get_mut(&msg.matching_id) {
+                recorder.record(
+                    game.tick,
+                    Utc::now(),
+                    ReplayEventKind::Input {
+                        player_id: player_id.clone(),
+                        action: action.clone(),
+                    },
+                );
+            }
 
             // クローンしたアクションで通知を分岐
             match action {
@@ -247,7 +778,11 @@ impl Handler<ProcessInput> for GameManager {
                     attack_type,
                     position,
                     direction,
-                } => {
+                    ..
+                } if matches!(attack_type, AttackType::Special) => {
+                    // 遠距離攻撃は弾を発射し、命中はティックごとのシミュレーションで判定
+                    let projectile = game.spawn_projectile(&player_id, &direction, Utc::now());
+
                     self.send_opponent_attack(
                         &msg.matching_id,
                         &player_id,
@@ -255,6 +790,42 @@ impl Handler<ProcessInput> for GameManager {
                         position,
                         direction,
                     );
+
+                    if let Some(projectile) = projectile {
+                        self.broadcast_projectile_spawned(&msg.matching_id, &projectile);
+                    }
+                }
+                crate::models::InputAction::Attack {
+                    attack_type,
+                    position,
+                    direction,
+                    view_time,
+                } => {
+                    // 近距離攻撃の命中はサーバー側で判定（防御側の位置はview_timeまで巻き戻す）
+                    let judgement = game.judge_attack(
+                        &player_id,
+                        &attack_type,
+                        &direction,
+                        view_time,
+                        Utc::now(),
+                    );
+
+                    self.send_opponent_attack(
+                        &msg.matching_id,
+                        &player_id,
+                        attack_type.clone(),
+                        position,
+                        direction,
+                    );
+
+                    if let Some(judgement) = judgement {
+                        self.broadcast_attack_result(
+                            &msg.matching_id,
+                            &player_id,
+                            attack_type,
+                            judgement,
+                        );
+                    }
                 }
                 _ => {
                     // Attack以外はこれまで通り、更新後の状態で通知
@@ -273,14 +844,73 @@ pub struct ProcessStateUpdate {
     pub player_id: String,
     pub position: crate::models::Vector3,
     pub rotation: crate::models::Vector3,
+    pub seq: Option<u32>,
 }
 
 impl Handler<ProcessStateUpdate> for GameManager {
     type Result = ();
 
     fn handle(&mut self, msg: ProcessStateUpdate, _ctx: &mut Self::Context) {
-        if let Some(game) = self.games.get_mut(&msg.matching_id) {
-            game.update_state(&msg.player_id, msg.position, msg.rotation);
+        if let Some(game) = self
+            .games
+            .get_mut(&msg.matching_id)
+            .filter(|game| !game.is_paused())
+        {
+            // 脱落したプレイヤーの状態更新は破棄
+            if !game.can_act(&msg.player_id) {
+                println!(
+                    "⚠️ Dropped StateUpdate from eliminated player: player_id={}",
+                    msg.player_id
+                );
+                return;
+            }
+
+            // 重複・順序逆転した状態更新は破棄
+            if !game.accept_sequence(&msg.player_id, msg.seq) {
+                println!(
+                    "⚠️ Dropped out-of-order StateUpdate: player_id={}, seq={:?}, last_processed_seq={:?}",
+                    msg.player_id,
+                    msg.seq,
+                    game.last_processed_seq(&msg.player_id)
+                );
+                return;
+            }
+
+            let validation =
+                game.update_state(&msg.player_id, msg.position, msg.rotation, Utc::now());
+
+            // 受理した（補正後の）位置をリプレイに記録
+            if let (Some(recorder), Some(character)) = (
+                self.recorders.get_mut(&msg.matching_id),
+                game.character(&msg.player_id),
+            ) {
+                recorder.record(
+                    game.tick,
+                    Utc::now(),
+                    ReplayEventKind::StateUpdate {
+                        player_id: msg.player_id.clone(),
+                        position: character.position.clone(),
+                        rotation: character.rotation.clone(),
+                    },
+                );
+            }
+
+            // 速度超過の場合は送信者に正規の位置を通知
+            if let Some(MoveValidation::Corrected(position)) = validation {
+                println!(
+                    "⚠️ Position corrected: player_id={}, position={:?}",
+                    msg.player_id, position
+                );
+                if let Some(character) = game.character(&msg.player_id) {
+                    let correction = WsMessage::PositionCorrection {
+                        position,
+                        rotation: character.rotation.clone(),
+                        last_processed_seq: game.last_processed_seq(&msg.player_id),
+                        timestamp: Utc::now(),
+                    };
+                    self.send_to_player(&msg.matching_id, &msg.player_id, correction);
+                }
+            }
 
             // 状態更新後、相手に通知
             self.send_opponent_state_for_player(&msg.matching_id, &msg.player_id);
@@ -288,21 +918,313 @@ impl Handler<ProcessStateUpdate> for GameManager {
     }
 }
 
+// メッセージ: 降参
+#[derive(Message)]
+#[rtype(result = "()")]
+pub struct Surrender {
+    pub matching_id: Uuid,
+    pub player_id: String, // 降参したプレイヤー
+}
+
+impl Handler<Surrender> for GameManager {
+    type Result = ();
+
+    fn handle(&mut self, msg: Surrender, _ctx: &mut Self::Context) {
+        let now = Utc::now();
+        let Some(game) = self
+            .games
+            .get_mut(&msg.matching_id)
+            .filter(|game| game.active_players().contains(&msg.player_id.as_str()))
+        else {
+            return;
+        };
+        let result = game.surrender(&msg.player_id, now);
+
+        println!(
+            "🏳️ Player surrendered: matching_id={}, player_id={}",
+            msg.matching_id, msg.player_id
+        );
+        match result {
+            Some(result) => self.finish_game(&msg.matching_id, result),
+            // 残りのプレイヤーが2人以上なら脱落を通知して続行
+            None => self.broadcast_player_eliminated(
+                &msg.matching_id,
+                &msg.player_id,
+                EndReason::Surrender,
+                now,
+            ),
+        }
+    }
+}
+
+// メッセージ: スナップショットのACK
+#[derive(Message)]
+#[rtype(result = "()")]
+pub struct AcknowledgeSnapshot {
+    pub matching_id: Uuid,
+    pub player_id: String,
+    pub tick: u64,
+}
+
+impl Handler<AcknowledgeSnapshot> for GameManager {
+    type Result = ();
+
+    fn handle(&mut self, msg: AcknowledgeSnapshot, _ctx: &mut Self::Context) {
+        if let Some(tracker) = self
+            .games
+            .get_mut(&msg.matching_id)
+            .and_then(|game| game.snapshots.as_mut())
+        {
+            tracker.acknowledge(&msg.player_id, msg.tick);
+        }
+    }
+}
+
+// メッセージ: ゲーム中のプレイヤー切断
+#[derive(Message)]
+#[rtype(result = "()")]
+pub struct PlayerDisconnected {
+    pub matching_id: Uuid,
+    pub player_id: String,
+}
+
+impl Handler<PlayerDisconnected> for GameManager {
+    type Result = ();
+
+    fn handle(&mut self, msg: PlayerDisconnected, _ctx: &mut Self::Context) {
+        let now = Utc::now();
+        let Some(deadline) = self
+            .games
+            .get_mut(&msg.matching_id)
+            .and_then(|game| game.pause_for_disconnect(&msg.player_id, now))
+        else {
+            return;
+        };
+
+        println!(
+            "⏸️ Player disconnected mid-game, pausing: matching_id={}, player_id={}, reconnect_deadline={}",
+            msg.matching_id, msg.player_id, deadline
+        );
+        self.send_disconnect_countdown(&msg.matching_id, now);
+    }
+}
+
+// メッセージ: 再接続したプレイヤーの送信チャンネルを差し替え
+#[derive(Message)]
+#[rtype(result = "()")]
+pub struct RebindPlayer {
+    pub matching_id: Uuid,
+    pub player_id: String,
+    pub sender: mpsc::UnboundedSender<WsMessage>,
+}
+
+impl Handler<RebindPlayer> for GameManager {
+    type Result = ();
+
+    fn handle(&mut self, msg: RebindPlayer, _ctx: &mut Self::Context) {
+        let now = Utc::now();
+        let Some(game) = self
+            .games
+            .get_mut(&msg.matching_id)
+            .filter(|game| game.character(&msg.player_id).is_some())
+        else {
+            return;
+        };
+
+        // 切断中だった場合は一時停止を解除
+        let was_disconnected = game.resume_player(&msg.player_id, now);
+        println!(
+            "🔗 Rebinding player: matching_id={}, player_id={}, was_disconnected={}, paused={}",
+            msg.matching_id,
+            msg.player_id,
+            was_disconnected,
+            game.is_paused()
+        );
+
+        // 再接続したクライアントに現在の状態を送信
+        if let (Some(your_character), Some(opponent_character)) = (
+            game.character(&msg.player_id),
+            game.first_opponent_character(&msg.player_id),
+        ) {
+            let resync = WsMessage::GameResync {
+                your_player_id: msg.player_id.clone(),
+                your_character: your_character.clone(),
+                last_processed_seq: game.last_processed_seq(&msg.player_id),
+                opponent_character: opponent_character.clone(),
+                opponent_characters: game.opponent_characters(&msg.player_id),
+                teams: game.teams.clone(),
+                round: game.current_round,
+                rounds: game.rounds,
+                scores: game.scores.clone(),
+                time_limit_seconds: game.time_limit_seconds,
+                elapsed_seconds: game.elapsed_seconds(now),
+                remaining_seconds: game.remaining_seconds(now),
+                paused: game.is_paused(),
+                arena_id: game.arena.as_ref().map(|arena| arena.id.clone()),
+                timestamp: now,
+            };
+            let _ = msg.sender.send(resync);
+        }
+
+        // 再接続したクライアントには次回スナップショットを全量で送る
+        if let Some(tracker) = game.snapshots.as_mut() {
+            tracker.reset_ack(&msg.player_id);
+        }
+
+        // 古いチャンネルを新しいものに差し替え
+        self.ws_senders
+            .entry(msg.matching_id)
+            .or_default()
+            .insert(msg.player_id.clone(), msg.sender);
+
+        if was_disconnected {
+            let reconnected = WsMessage::OpponentReconnected {
+                player_id: msg.player_id.clone(),
+                timestamp: now,
+            };
+            self.send_to_others(&msg.matching_id, &msg.player_id, reconnected);
+        }
+    }
+}
+
+// メッセージ: 観戦者の登録
+// 成功した場合は観戦者数（自分を含む）を返す
+#[derive(Message)]
+#[rtype(result = "Result<usize, String>")]
+pub struct AddSpectator {
+    pub matching_id: Uuid,
+    pub spectator_id: Uuid, // 観戦者のセッションID
+    pub sender: mpsc::UnboundedSender<WsMessage>,
+}
+
+impl Handler<AddSpectator> for GameManager {
+    type Result = Result<usize, String>;
+
+    fn handle(&mut self, msg: AddSpectator, _ctx: &mut Self::Context) -> Self::Result {
+        let now = Utc::now();
+        let status = self
+            .sessions
+            .lock()
+            .map_err(|_| "Failed to lock sessions".to_string())?
+            .get(&msg.matching_id)
+            .filter(|session| session.is_valid())
+            .map(|session| session.status.clone())
+            .ok_or_else(|| "Matching not found".to_string())?;
+        if status == MatchingStatus::Finished {
+            return Err("Match is already finished".to_string());
+        }
+
+        let spectators = self.spectators.entry(msg.matching_id).or_default();
+        if !spectators.contains_key(&msg.spectator_id)
+            && spectators.len() >= MAX_SPECTATORS_PER_MATCH
+        {
+            return Err(format!(
+                "Spectator limit reached ({} per match)",
+                MAX_SPECTATORS_PER_MATCH
+            ));
+        }
+        spectators.insert(msg.spectator_id, msg.sender.clone());
+        let spectator_count = spectators.len();
+
+        println!(
+            "👀 Spectator joined: matching_id={}, spectator_id={}, spectators={}",
+            msg.matching_id, msg.spectator_id, spectator_count
+        );
+        let _ = msg.sender.send(WsMessage::SpectateStarted {
+            matching_id: msg.matching_id,
+            status,
+            spectator_count,
+            timestamp: now,
+        });
+
+        // ゲーム中なら現在の状態を送信（開始前なら1ラウンド目の開始時に送信される）
+        if let Some(sync) = self.spectator_sync(&msg.matching_id, now) {
+            let _ = msg.sender.send(sync);
+        }
+        Ok(spectator_count)
+    }
+}
+
+// メッセージ: 観戦者の登録解除
+#[derive(Message)]
+#[rtype(result = "()")]
+pub struct RemoveSpectator {
+    pub matching_id: Uuid,
+    pub spectator_id: Uuid,
+}
+
+impl Handler<RemoveSpectator> for GameManager {
+    type Result = ();
+
+    fn handle(&mut self, msg: RemoveSpectator, _ctx: &mut Self::Context) {
+        let Some(spectators) = self.spectators.get_mut(&msg.matching_id) else {
+            return;
+        };
+        if spectators.remove(&msg.spectator_id).is_none() {
+            return;
+        }
+        println!(
+            "👋 Spectator left: matching_id={}, spectator_id={}, spectators={}",
+            msg.matching_id,
+            msg.spectator_id,
+            spectators.len()
+        );
+        if spectators.is_empty() {
+            self.spectators.remove(&msg.matching_id);
+        }
+    }
+}
+
 // メッセージ: ダメージ適用
 #[derive(Message)]
 #[rtype(result = "()")]
 pub struct ApplyDamage {
     pub matching_id: Uuid,
-    pub player_id: String,
-    pub damage: i32,
+    pub player_id: String, // 被弾したプレイヤー
+    pub attack_type: crate::models::AttackType,
 }
 
 impl Handler<ApplyDamage> for GameManager {
     type Result = ();
 
     fn handle(&mut self, msg: ApplyDamage, _ctx: &mut Self::Context) {
-        if let Some(game) = self.games.get_mut(&msg.matching_id) {
-            game.apply_damage(&msg.player_id, msg.damage);
+        // 切断による一時停止中は、停止前に命中した攻撃の被弾報告も適用しない
+        if let Some(game) = self
+            .games
+            .get_mut(&msg.matching_id)
+            .filter(|game| !game.is_paused())
+        {
+            // サーバーが外れと判定した攻撃への被弾報告はフラグを立てて無視
+            let Some(attacker_id) =
+                game.take_confirmed_hit(&msg.player_id, &msg.attack_type, Utc::now())
+            else {
+                let count = game.flag_damage_report(&msg.player_id);
+                println!(
+                    "🚩 Flagged DamageReport without confirmed hit: matching_id={}, player_id={}, attack_type={}, count={}",
+                    msg.matching_id, msg.player_id, msg.attack_type, count
+                );
+                self.send_to_player(
+                    &msg.matching_id,
+                    &msg.player_id,
+                    WsMessage::Error {
+                        message: "Damage report rejected: no confirmed hit".to_string(),
+                    },
+                );
+                return;
+            };
+
+            // ダメージ量は攻撃者の攻撃力と防御力からサーバー側で計算
+            if let Some(damage) =
+                game.apply_attack_damage(&attacker_id, &msg.player_id, &msg.attack_type)
+            {
+                // 全員のUIを同期するためHPを通知
+                self.broadcast_hp_update(&msg.matching_id, &msg.player_id, &damage);
+            } else {
+                println!(
+                    "⚠️ ApplyDamage for unknown player: matching_id={}, player_id={}",
+                    msg.matching_id, msg.player_id
+                );
+            }
         }
     }
 }
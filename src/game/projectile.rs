@@ -0,0 +1,86 @@
+use crate::game::state::DamageResult;
+use crate::models::Vector3;
+use crate::utils::{add_vector3, multiply_vector3, normalize_vector3, vector3_length};
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+/// 弾の速度（単位/秒）
+pub const PROJECTILE_SPEED: f32 = 20.0;
+/// 弾の寿命（ms）
+pub const PROJECTILE_LIFETIME_MS: i64 = 1500;
+/// 弾の当たり判定の半径
+pub const PROJECTILE_RADIUS: f32 = 0.3;
+
+/// 遠距離攻撃（Special）で発射された弾
+#[derive(Debug, Clone)]
+pub struct Projectile {
+    pub id: Uuid,
+    /// 発射したプレイヤー（自分の弾には当たらない）
+    pub owner_id: String,
+    pub position: Vector3,
+    /// 速度（単位/秒）
+    pub velocity: Vector3,
+    pub spawned_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+    /// 最後に位置を更新した時刻
+    pub updated_at: DateTime<Utc>,
+}
+
+impl Projectile {
+    /// `direction` 方向に弾を生成（向きが不正な場合はNone）
+    pub fn spawn(
+        owner_id: &str,
+        position: Vector3,
+        direction: &Vector3,
+        now: DateTime<Utc>,
+    ) -> Option<Self> {
+        let direction = normalize_vector3(direction);
+        if vector3_length(&direction) == 0.0 {
+            return None;
+        }
+
+        Some(Self {
+            id: Uuid::new_v4(),
+            owner_id: owner_id.to_string(),
+            position,
+            velocity: multiply_vector3(&direction, PROJECTILE_SPEED),
+            spawned_at: now,
+            expires_at: now + chrono::Duration::milliseconds(PROJECTILE_LIFETIME_MS),
+            updated_at: now,
+        })
+    }
+
+    /// `now` までの移動後の位置を計算（寿命を超えては進まない）
+    pub fn next_position(&self, now: DateTime<Utc>) -> Vector3 {
+        let until = now.min(self.expires_at);
+        let elapsed_secs = (until
+            .signed_duration_since(self.updated_at)
+            .num_milliseconds()
+            .max(0) as f32)
+            / 1000.0;
+        add_vector3(
+            &self.position,
+            &multiply_vector3(&self.velocity, elapsed_secs),
+        )
+    }
+
+    /// 寿命が尽きたか
+    pub fn is_expired(&self, now: DateTime<Utc>) -> bool {
+        now >= self.expires_at
+    }
+}
+
+/// 弾のシミュレーション結果
+#[derive(Debug, Clone)]
+pub enum ProjectileEvent {
+    /// 弾がプレイヤーに命中した（ダメージは適用済み）
+    Hit {
+        projectile: Projectile,
+        target_id: String,
+        damage: DamageResult,
+    },
+    /// 障害物に遮られて消滅した
+    Blocked { projectile: Projectile },
+    /// 何にも当たらずに寿命が尽きた
+    Expired { projectile: Projectile },
+}
@@ -0,0 +1,202 @@
+use crate::game::state::GameStateManager;
+use crate::models::{GameResult, ReplayEvent, ReplayEventKind, Team};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::path::PathBuf;
+use uuid::Uuid;
+
+/// リプレイファイルの保存ディレクトリ
+pub const REPLAY_DATA_DIR: &str = "data/replays";
+/// 1試合で記録するイベント数の上限（超えた分は記録せず`truncated`にする）
+pub const MAX_REPLAY_EVENTS: usize = 200_000;
+/// 再生速度の省略時の値（等速）
+pub const DEFAULT_REPLAY_SPEED: f32 = 1.0;
+/// 設定可能な再生速度の範囲
+pub const MIN_REPLAY_SPEED: f32 = 0.25;
+pub const MAX_REPLAY_SPEED: f32 = 8.0;
+/// リプレイ再生時にイベントを送信する間隔（ms）
+pub const REPLAY_FRAME_INTERVAL_MS: u64 = 16;
+
+/// 1試合分のリプレイ
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Replay {
+    pub matching_id: Uuid,
+    /// 試合ごとのID（再戦しても前の試合のリプレイを上書きしない）
+    pub game_id: Uuid,
+    /// 参加プレイヤーID（参加順）
+    pub player_ids: Vec<String>,
+    /// 各プレイヤーのチーム（チーム戦以外は空）
+    pub teams: HashMap<String, Team>,
+    pub arena_id: Option<String>,
+    pub rounds: u32,
+    pub time_limit_seconds: i64,
+    pub started_at: DateTime<Utc>,
+    pub finished_at: Option<DateTime<Utc>>,
+    /// 試合開始から終了までの時間（ms）
+    pub duration_ms: i64,
+    pub result: Option<GameResult>,
+    /// イベント数が上限に達し、以降のイベントが記録されていないか
+    pub truncated: bool,
+    /// 経過時間順のイベント
+    pub events: Vec<ReplayEvent>,
+}
+
+/// 試合中のイベントを記録する
+#[derive(Debug, Clone)]
+pub struct ReplayRecorder {
+    replay: Replay,
+}
+
+impl ReplayRecorder {
+    /// 開始するゲームの情報から記録を開始
+    pub fn new(game: &GameStateManager, now: DateTime<Utc>) -> Self {
+        Self {
+            replay: Replay {
+                matching_id: game.matching_id,
+                game_id: game.game_id,
+                player_ids: game.player_ids.clone(),
+                teams: game.teams.clone(),
+                arena_id: game.arena.as_ref().map(|arena| arena.id.clone()),
+                rounds: game.rounds,
+                time_limit_seconds: game.time_limit_seconds,
+                started_at: now,
+                finished_at: None,
+                duration_ms: 0,
+                result: None,
+                truncated: false,
+                events: Vec::new(),
+            },
+        }
+    }
+
+    /// 記録開始からの経過時間（ms）
+    fn offset_ms(&self, now: DateTime<Utc>) -> i64 {
+        now.signed_duration_since(self.replay.started_at)
+            .num_milliseconds()
+            .max(0)
+    }
+
+    /// イベントを記録（上限に達している場合は破棄）
+    pub fn record(&mut self, tick: u64, now: DateTime<Utc>, event: ReplayEventKind) {
+        if self.replay.events.len() >= MAX_REPLAY_EVENTS {
+            self.replay.truncated = true;
+            return;
+        }
+        let offset_ms = self.offset_ms(now);
+        self.replay.events.push(ReplayEvent {
+            tick,
+            offset_ms,
+            event,
+        });
+    }
+
+    /// 試合結果を付けて記録を終了
+    pub fn finish(mut self, result: GameResult, now: DateTime<Utc>) -> Replay {
+        self.replay.duration_ms = self.offset_ms(now);
+        self.replay.finished_at = Some(now);
+        self.replay.result = Some(result);
+        self.replay
+    }
+}
+
+/// リプレイのファイル保存先（`<dir>/<game_id>.json`）
+#[derive(Debug, Clone)]
+pub struct ReplayStore {
+    dir: PathBuf,
+}
+
+impl ReplayStore {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// 試合IDに対応するリプレイファイルのパス
+    pub fn path(&self, game_id: &Uuid) -> PathBuf {
+        self.dir.join(format!("{}.json", game_id))
+    }
+
+    /// リプレイを保存し、保存先のパスを返す
+    pub async fn save(&self, replay: &Replay) -> std::io::Result<PathBuf> {
+        tokio::fs::create_dir_all(&self.dir).await?;
+        let path = self.path(&replay.game_id);
+        let json = serde_json::to_vec(replay)?;
+        tokio::fs::write(&path, json).await?;
+        Ok(path)
+    }
+
+    /// リプレイを読み込む（存在しない場合はNone）
+    pub async fn load(&self, game_id: &Uuid) -> std::io::Result<Option<Replay>> {
+        let json = match tokio::fs::read(self.path(game_id)).await {
+            Ok(json) => json,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e),
+        };
+        Ok(Some(serde_json::from_slice(&json)?))
+    }
+}
+
+/// 再生速度の妥当性を検証
+pub fn validate_replay_speed(speed: f32) -> Result<f32, String> {
+    if !(MIN_REPLAY_SPEED..=MAX_REPLAY_SPEED).contains(&speed) {
+        return Err(format!(
+            "speed must be between {} and {}",
+            MIN_REPLAY_SPEED, MAX_REPLAY_SPEED
+        ));
+    }
+    Ok(speed)
+}
+
+/// リプレイの再生位置の管理
+/// 実時間の経過に再生速度を掛けて再生位置を進め、到達したイベントを返す
+#[derive(Debug, Clone)]
+pub struct ReplayPlayback {
+    pub matching_id: Uuid,
+    pub speed: f32,
+    /// 現在の再生位置（試合開始からのms）
+    position_ms: f64,
+    events: VecDeque<ReplayEvent>,
+    result: Option<GameResult>,
+}
+
+impl ReplayPlayback {
+    pub fn new(replay: Replay, speed: f32) -> Self {
+        Self {
+            matching_id: replay.matching_id,
+            speed,
+            position_ms: 0.0,
+            events: replay.events.into(),
+            result: replay.result,
+        }
+    }
+
+    /// 再生速度を変更（以降の経過時間から適用）
+    pub fn set_speed(&mut self, speed: f32) {
+        self.speed = speed;
+    }
+
+    /// 実時間で `elapsed_ms` 経過した分だけ再生位置を進め、到達したイベントを返す
+    pub fn advance(&mut self, elapsed_ms: f64) -> Vec<ReplayEvent> {
+        self.position_ms += elapsed_ms * self.speed as f64;
+        let position_ms = self.position_ms;
+        let mut due = Vec::new();
+        while self
+            .events
+            .front()
+            .is_some_and(|event| event.offset_ms as f64 <= position_ms)
+        {
+            due.extend(self.events.pop_front());
+        }
+        due
+    }
+
+    /// 全イベントを再生し終えたか
+    pub fn is_finished(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    /// 記録された試合結果
+    pub fn result(&self) -> Option<&GameResult> {
+        self.result.as_ref()
+    }
+}
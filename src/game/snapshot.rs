@@ -0,0 +1,114 @@
+use crate::models::{Character, CharacterDelta, Vector3};
+use std::collections::{HashMap, VecDeque};
+
+/// 保持するスナップショット履歴の数（ACKがこれより古い場合は全量を送信）
+const SNAPSHOT_HISTORY_SIZE: usize = 64;
+
+/// 差分計算用のキャラクター状態
+#[derive(Debug, Clone)]
+struct CharacterSnapshot {
+    position: Vector3,
+    rotation: Vector3,
+    hp: i32,
+}
+
+impl From<&Character> for CharacterSnapshot {
+    fn from(character: &Character) -> Self {
+        Self {
+            position: character.position.clone(),
+            rotation: character.rotation.clone(),
+            hp: character.hp,
+        }
+    }
+}
+
+/// スナップショット配信の状態管理
+/// 送信したスナップショットの履歴と、プレイヤーごとの最終ACKを保持する
+#[derive(Debug, Clone)]
+pub struct SnapshotTracker {
+    /// 何ティックごとに送信するか
+    pub interval_ticks: u32,
+    /// 送信済みスナップショット (tick, player_id -> 状態)
+    history: VecDeque<(u64, HashMap<String, CharacterSnapshot>)>,
+    /// プレイヤーごとの最終ACKティック
+    acked_ticks: HashMap<String, u64>,
+}
+
+impl SnapshotTracker {
+    pub fn new(interval_ticks: u32) -> Self {
+        Self {
+            interval_ticks: interval_ticks.max(1),
+            history: VecDeque::new(),
+            acked_ticks: HashMap::new(),
+        }
+    }
+
+    /// このティックでスナップショットを送信するか
+    pub fn is_due(&self, tick: u64) -> bool {
+        tick.is_multiple_of(self.interval_ticks as u64)
+    }
+
+    /// 現在のキャラクター状態を履歴に記録
+    pub fn record(&mut self, tick: u64, characters: &[(&str, &Character)]) {
+        let states = characters
+            .iter()
+            .map(|(player_id, character)| {
+                (player_id.to_string(), CharacterSnapshot::from(*character))
+            })
+            .collect();
+        self.history.push_back((tick, states));
+        while self.history.len() > SNAPSHOT_HISTORY_SIZE {
+            self.history.pop_front();
+        }
+    }
+
+    /// クライアントのACKを記録（古いACKや未送信のティックは無視）
+    pub fn acknowledge(&mut self, player_id: &str, tick: u64) -> bool {
+        let is_known = self.history.iter().any(|(t, _)| *t == tick);
+        let acked = self.acked_ticks.entry(player_id.to_string()).or_insert(0);
+        if !is_known || tick <= *acked {
+            return false;
+        }
+        *acked = tick;
+        true
+    }
+
+    /// ACKをリセットし、次回は全量を送信する（再接続時など）
+    pub fn reset_ack(&mut self, player_id: &str) {
+        self.acked_ticks.remove(player_id);
+    }
+
+    /// 指定プレイヤー向けのスナップショットを生成
+    /// ACK済みのスナップショットが履歴にあれば、そこから変化したフィールドのみを含める
+    /// 戻り値は (差分の基準ティック, キャラクター差分)
+    pub fn delta_for(
+        &self,
+        player_id: &str,
+        characters: &[(&str, &Character)],
+    ) -> (Option<u64>, Vec<CharacterDelta>) {
+        let baseline = self.acked_ticks.get(player_id).and_then(|acked| {
+            self.history
+                .iter()
+                .find(|(tick, _)| tick == acked)
+                .map(|(tick, states)| (*tick, states))
+        });
+
+        let deltas = characters
+            .iter()
+            .filter_map(|(id, character)| {
+                let previous = baseline.and_then(|(_, states)| states.get(*id));
+                let delta = CharacterDelta {
+                    player_id: id.to_string(),
+                    position: (previous.map(|p| &p.position) != Some(&character.position))
+                        .then(|| character.position.clone()),
+                    rotation: (previous.map(|p| &p.rotation) != Some(&character.rotation))
+                        .then(|| character.rotation.clone()),
+                    hp: (previous.map(|p| p.hp) != Some(character.hp)).then_some(character.hp),
+                };
+                (!delta.is_empty()).then_some(delta)
+            })
+            .collect();
+
+        (baseline.map(|(tick, _)| tick), deltas)
+    }
+}
@@ -1,84 +1,872 @@
-use crate::models::{Character, GameState, InputAction, PlayerInput};
-use crate::utils::{add_vector3, multiply_vector3, normalize_vector3};
-use chrono::Utc;
+use crate::game::arena::Arena;
+use crate::game::combat::{is_attack_hit, is_projectile_hit};
+use crate::game::projectile::{PROJECTILE_RADIUS, Projectile, ProjectileEvent};
+use crate::game::snapshot::SnapshotTracker;
+use crate::models::{
+    AttackType, Character, DEFAULT_ROUNDS, DEFAULT_TIME_LIMIT_SECONDS, EndReason, GameOutcome,
+    GameResult, GameState, InputAction, MonsterStats, PlayerInput, SizeType, Team, Vector3,
+};
+use crate::utils::{add_vector3, distance, multiply_vector3, normalize_vector3, subtract_vector3};
+use chrono::{DateTime, Utc};
+use std::collections::{HashMap, VecDeque};
 use uuid::Uuid;
 
+/// ダメージ計算機（サーバー側でダメージを決定し、クライアントの改ざんを防ぐ）
+#[derive(Debug, Clone)]
+pub struct DamageCalculator {
+    /// 近距離攻撃（Normal）の攻撃力倍率
+    pub short_range_multiplier: f32,
+    /// 遠距離攻撃（Special）の攻撃力倍率
+    pub long_range_multiplier: f32,
+    /// 防御力1あたりの軽減量
+    pub defense_factor: f32,
+    /// クリティカル発生率（0.0〜1.0）
+    pub critical_rate: f32,
+    /// クリティカル時のダメージ倍率
+    pub critical_multiplier: f32,
+    /// 最低保証ダメージ
+    pub min_damage: i32,
+}
+
+impl Default for DamageCalculator {
+    fn default() -> Self {
+        Self {
+            short_range_multiplier: 1.0,
+            long_range_multiplier: 1.0,
+            defense_factor: 0.5,
+            critical_rate: 0.1,
+            critical_multiplier: 1.5,
+            min_damage: 1,
+        }
+    }
+}
+
+/// ダメージ計算結果
+#[derive(Debug, Clone, PartialEq)]
+pub struct DamageResult {
+    pub damage: i32,
+    pub is_critical: bool,
+}
+
+impl DamageCalculator {
+    /// 攻撃側・防御側のステータスからダメージを計算
+    pub fn calculate(
+        &self,
+        attacker: &MonsterStats,
+        defender: &MonsterStats,
+        attack_type: &AttackType,
+    ) -> DamageResult {
+        self.calculate_with_roll(attacker, defender, attack_type, rand::random::<f32>())
+    }
+
+    /// クリティカル判定用の乱数値(0.0〜1.0)を指定してダメージを計算
+    pub fn calculate_with_roll(
+        &self,
+        attacker: &MonsterStats,
+        defender: &MonsterStats,
+        attack_type: &AttackType,
+        roll: f32,
+    ) -> DamageResult {
+        // 攻撃種別で攻撃力を選択（Normal: 近距離, Special: 遠距離）
+        let attack_power = match attack_type {
+            AttackType::Normal => {
+                attacker.short_range_attack_power as f32 * self.short_range_multiplier
+            }
+            AttackType::Special => {
+                attacker.long_range_attack_power as f32 * self.long_range_multiplier
+            }
+        };
+
+        let mut damage = attack_power - defender.defense_power as f32 * self.defense_factor;
+
+        let is_critical = roll < self.critical_rate;
+        if is_critical {
+            damage *= self.critical_multiplier;
+        }
+
+        DamageResult {
+            damage: (damage.round() as i32).max(self.min_damage),
+            is_critical,
+        }
+    }
+}
+
+/// 移動速度の許容倍率（通信揺らぎ・フレーム誤差の吸収用）
+const MOVE_SPEED_TOLERANCE: f32 = 1.2;
+/// 経過時間に依らず許容する移動距離
+/// 使い切らなかった許容距離はこの値を上限に次のStateUpdateへ持ち越す（送信頻度で許容距離が増えないようにする）
+const MOVE_DISTANCE_SLACK: f32 = 0.5;
+
+/// StateUpdateの移動検証結果
+#[derive(Debug, Clone)]
+pub enum MoveValidation {
+    /// 送信された位置をそのまま受理
+    Accepted,
+    /// 速度超過のため補正（補正後の正規の位置）
+    Corrected(Vector3),
+}
+
+/// 命中判定から被弾報告までの有効時間（ms）
+const HIT_CONFIRM_WINDOW_MS: i64 = 2000;
+
+/// 位置履歴の保持期間（ms）
+const POSITION_HISTORY_MS: i64 = 1000;
+/// ラグ補償で防御側を巻き戻せる最大時間（ms）
+pub const MAX_REWIND_MS: i64 = 250;
+
+/// サーバーによる攻撃の命中判定結果
+#[derive(Debug, Clone)]
+pub struct AttackJudgement {
+    pub defender_id: String,
+    pub hit: bool,
+    /// 射程・向きは満たしていたが、障害物に遮られて外れたか
+    pub blocked: bool,
+}
+
+/// 切断したプレイヤーの再接続を待つ時間（秒）
+pub const DISCONNECT_GRACE_SECONDS: i64 = 15;
+
+/// ラウンドの決着
+#[derive(Debug, Clone)]
+pub struct RoundResult {
+    pub round: u32,
+    pub winner_id: Option<String>,  // 引き分け・チーム戦の場合はNone
+    pub winning_team: Option<Team>, // チーム戦で勝利したチーム
+    pub end_reason: EndReason,
+}
+
+/// ラウンド終了後の試合の進行
+#[derive(Debug)]
+pub enum RoundProgress {
+    /// 次のラウンドを開始した
+    NextRound,
+    /// 試合終了（最終結果）
+    MatchOver(GameResult),
+}
+
+/// サーバーが命中と判定した攻撃 (attacker_id, 判定時刻)
+type ConfirmedHit = (String, DateTime<Utc>);
+
 pub struct GameStateManager {
     pub matching_id: Uuid,
-    pub player_a_id: String,
-    pub player_b_id: String,
-    pub player_a_character: Character,
-    pub player_b_character: Character,
+    /// 試合ごとのID（再戦では新しい試合として別のIDになる）
+    pub game_id: Uuid,
+    /// 参加プレイヤーID（参加順）
+    pub player_ids: Vec<String>,
+    /// プレイヤーごとのキャラクター (player_id -> キャラクター)
+    pub characters: HashMap<String, Character>,
+    /// ダメージ計算パラメータ
+    pub damage_calculator: DamageCalculator,
+    /// ゲーム開始時刻（経過時間の計算用。一時停止していた時間の分だけ後ろにずれる）
+    pub started_at: DateTime<Utc>,
+    /// 実際のゲーム開始時刻（一時停止しても変わらない。試合記録に保存する）
+    pub actual_started_at: DateTime<Utc>,
+    /// 現在のラウンドの開始時刻
+    pub round_started_at: DateTime<Utc>,
+    /// 攻撃種別ごとの最終攻撃時刻 ((player_id, attack_type) -> 時刻)
+    last_attack_at: HashMap<(String, AttackType), DateTime<Utc>>,
+    /// 最後に受理したStateUpdateの時刻 (player_id -> 時刻)
+    last_state_update_at: HashMap<String, DateTime<Utc>>,
+    /// 次のStateUpdateに持ち越す許容移動距離 (player_id -> 距離、最大MOVE_DISTANCE_SLACK)
+    move_slack: HashMap<String, f32>,
+    /// ラグ補償用の位置履歴 (player_id -> (時刻, 位置)のリングバッファ)
+    position_history: HashMap<String, VecDeque<(DateTime<Utc>, Vector3)>>,
+    /// サーバーが命中と判定した攻撃 ((defender_id, attack_type) -> 判定順の(attacker_id, 判定時刻))
+    /// 複数の攻撃者が同じ相手に命中させても、被弾報告ごとに1件ずつ消費する
+    confirmed_hits: HashMap<(String, AttackType), VecDeque<ConfirmedHit>>,
+    /// 命中判定と一致しない被弾報告の件数 (player_id -> 件数)
+    pub flagged_damage_reports: HashMap<String, u32>,
+    /// 1ラウンドの試合時間（秒）
+    pub time_limit_seconds: i64,
+    /// ラウンド数
+    pub rounds: u32,
+    /// 現在のラウンド（1始まり）
+    pub current_round: u32,
+    /// ラウンド勝利数 (player_id -> 勝利数)
+    pub scores: HashMap<String, u32>,
+    /// ラウンド開始時のキャラクター（ラウンド間のリセット用）
+    initial_characters: HashMap<String, Character>,
+    /// 現在のラウンドでHPが0になった順のプレイヤーID
+    pub eliminated: Vec<String>,
+    /// 降参・切断で脱落した順のプレイヤーID（以降のラウンドにも参加しない）
+    pub forfeited: Vec<String>,
+    /// 切断による一時停止の開始時刻
+    pub paused_at: Option<DateTime<Utc>>,
+    /// 切断中のプレイヤーの再接続期限 (player_id -> 期限)
+    pub disconnect_deadlines: HashMap<String, DateTime<Utc>>,
+    /// 最後に処理したシーケンス番号 (player_id -> seq)
+    last_processed_seq: HashMap<String, u32>,
+    /// ゲームループのティック番号（単調増加）
+    pub tick: u64,
+    /// スナップショット配信（Noneの場合はイベント駆動の通知のみ）
+    pub snapshots: Option<SnapshotTracker>,
+    /// 飛行中の弾（遠距離攻撃）
+    pub projectiles: Vec<Projectile>,
+    /// 対戦するアリーナ（Noneの場合は移動範囲の制限なし）
+    pub arena: Option<Arena>,
+    /// 各プレイヤーのチーム（空の場合は個人戦）
+    pub teams: HashMap<String, Team>,
+    /// チーム戦で味方への攻撃を有効にするか
+    pub friendly_fire: bool,
 }
 
 impl GameStateManager {
-    pub fn new(
-        matching_id: Uuid,
-        player_a_id: String,
-        player_b_id: String,
-        player_a_character: Character,
-        player_b_character: Character,
-    ) -> Self {
-        Self {
+    /// 参加順の (player_id, キャラクター) からゲームを作成
+    pub fn new(matching_id: Uuid, players: Vec<(String, Character)>) -> Self {
+        let now = Utc::now();
+        let player_ids: Vec<String> = players.iter().map(|(id, _)| id.clone()).collect();
+        let scores = player_ids.iter().map(|id| (id.clone(), 0)).collect();
+        let characters: HashMap<String, Character> = players.into_iter().collect();
+        let initial_characters = characters.clone();
+        let mut manager = Self {
             matching_id,
-            player_a_id,
-            player_b_id,
-            player_a_character,
-            player_b_character,
+            game_id: Uuid::new_v4(),
+            player_ids,
+            characters,
+            damage_calculator: DamageCalculator::default(),
+            started_at: now,
+            actual_started_at: now,
+            round_started_at: now,
+            last_attack_at: HashMap::new(),
+            last_state_update_at: HashMap::new(),
+            move_slack: HashMap::new(),
+            position_history: HashMap::new(),
+            confirmed_hits: HashMap::new(),
+            flagged_damage_reports: HashMap::new(),
+            time_limit_seconds: DEFAULT_TIME_LIMIT_SECONDS,
+            rounds: DEFAULT_ROUNDS,
+            current_round: 1,
+            scores,
+            initial_characters,
+            eliminated: Vec::new(),
+            forfeited: Vec::new(),
+            paused_at: None,
+            disconnect_deadlines: HashMap::new(),
+            last_processed_seq: HashMap::new(),
+            tick: 0,
+            snapshots: None,
+            projectiles: Vec::new(),
+            arena: None,
+            teams: HashMap::new(),
+            friendly_fire: false,
+        };
+        manager.record_initial_positions(now);
+        manager
+    }
+
+    /// 試合時間（秒）を設定
+    pub fn with_time_limit(mut self, time_limit_seconds: i64) -> Self {
+        self.time_limit_seconds = time_limit_seconds;
+        self
+    }
+
+    /// ラウンド数を設定
+    pub fn with_rounds(mut self, rounds: u32) -> Self {
+        self.rounds = rounds;
+        self
+    }
+
+    /// スナップショット送信間隔（ティック数）を設定
+    pub fn with_snapshot_interval(mut self, interval_ticks: Option<u32>) -> Self {
+        self.snapshots = interval_ticks.map(SnapshotTracker::new);
+        self
+    }
+
+    /// チーム分けと味方への攻撃の有効・無効を設定
+    pub fn with_teams(mut self, teams: HashMap<String, Team>, friendly_fire: bool) -> Self {
+        self.teams = teams;
+        self.friendly_fire = friendly_fire;
+        self
+    }
+
+    /// アリーナを設定し、キャラクターを参加順に初期配置に置く
+    pub fn with_arena(mut self, arena: Option<Arena>) -> Self {
+        if let Some(arena) = &arena {
+            for (index, player_id) in self.player_ids.iter().enumerate() {
+                let Some(character) = self.characters.get_mut(player_id) else {
+                    continue;
+                };
+                let spawn = arena.spawn_point(index);
+                character.position = spawn.position.clone();
+                character.rotation = spawn.rotation.clone();
+            }
+            self.initial_characters = self.characters.clone();
+            self.record_initial_positions(self.round_started_at);
         }
+        self.arena = arena;
+        self
+    }
+
+    /// ティックを進め、新しいティック番号を返す
+    pub fn advance_tick(&mut self) -> u64 {
+        self.tick += 1;
+        self.tick
+    }
+
+    /// クライアントのシーケンス番号を検証して記録
+    /// 最後に処理した番号以下（重複・順序逆転）の場合はfalseを返し、入力は破棄する
+    /// シーケンス番号のない入力は常に受理する
+    pub fn accept_sequence(&mut self, player_id: &str, seq: Option<u32>) -> bool {
+        let Some(seq) = seq else {
+            return true;
+        };
+        if self
+            .last_processed_seq
+            .get(player_id)
+            .is_some_and(|last| seq <= *last)
+        {
+            return false;
+        }
+        self.last_processed_seq.insert(player_id.to_string(), seq);
+        true
+    }
+
+    /// 最後に処理したシーケンス番号
+    pub fn last_processed_seq(&self, player_id: &str) -> Option<u32> {
+        self.last_processed_seq.get(player_id).copied()
     }
 
     /// プレイヤー入力を処理
-    pub fn process_input(&mut self, input: PlayerInput) {
-        let character = if input.player_id == self.player_a_id {
-            &mut self.player_a_character
-        } else if input.player_id == self.player_b_id {
-            &mut self.player_b_character
-        } else {
-            return; // 不明なプレイヤー
+    /// 攻撃がクールダウン中、またはNaN・無限大を含む入力の場合はエラーメッセージを返す
+    pub fn process_input(&mut self, input: PlayerInput) -> Result<(), String> {
+        let is_finite = match &input.action {
+            InputAction::Move { direction, speed } => direction.is_finite() && speed.is_finite(),
+            InputAction::Rotate { rotation } => rotation.is_finite(),
+            InputAction::Attack {
+                position,
+                direction,
+                ..
+            } => position.is_finite() && direction.is_finite(),
+        };
+        if !is_finite {
+            return Err("Invalid input: non-finite value".to_string());
+        }
+
+        if let InputAction::Attack { attack_type, .. } = &input.action {
+            self.register_attack(&input.player_id, attack_type, input.timestamp)?;
+        }
+
+        let bounds = self.arena.as_ref().map(|arena| arena.bounds.clone());
+        let Some(character) = self.character_mut(&input.player_id) else {
+            return Ok(()); // 不明なプレイヤー
         };
 
+        let is_move = matches!(input.action, InputAction::Move { .. });
         match input.action {
             InputAction::Move { direction, speed } => {
+                // 移動速度はモンスターのmove_speedを上限とする（負のmove_speedは0として扱う）
+                let speed = speed.clamp(0.0, character.stats.move_speed.max(0) as f32);
                 let normalized = normalize_vector3(&direction);
                 let velocity = multiply_vector3(&normalized, speed * 0.016667); // 1/60秒
-                character.position = add_vector3(&character.position, &velocity);
+                let position = add_vector3(&character.position, &velocity);
+                // アリーナの範囲外には出られない
+                character.position = match &bounds {
+                    Some(bounds) => bounds.clamp(&position),
+                    None => position,
+                };
             }
             InputAction::Rotate { rotation } => {
                 character.rotation = rotation;
             }
             InputAction::Attack { .. } => {
-                // 攻撃処理（ダメージ計算はクライアント側で実施）
-                // サーバーは攻撃イベントの検証のみ
+                // 攻撃処理（クールダウン判定は上で実施済み）
+                // ダメージは被弾側の報告を受けてサーバーで計算
+            }
+        }
+
+        if is_move {
+            self.record_position(&input.player_id, input.timestamp);
+        }
+        Ok(())
+    }
+
+    /// 攻撃クールダウン（Monster.attack_cooldown, ms）を判定し、攻撃可能なら攻撃時刻を記録
+    pub fn register_attack(
+        &mut self,
+        player_id: &str,
+        attack_type: &AttackType,
+        now: DateTime<Utc>,
+    ) -> Result<(), String> {
+        let cooldown_ms = self
+            .character(player_id)
+            .ok_or_else(|| format!("Unknown player: {}", player_id))?
+            .stats
+            .attack_cooldown;
+
+        let key = (player_id.to_string(), attack_type.clone());
+        if let Some(last_attack_at) = self.last_attack_at.get(&key) {
+            let elapsed_ms = now
+                .signed_duration_since(*last_attack_at)
+                .num_milliseconds();
+            if elapsed_ms < cooldown_ms {
+                return Err(format!(
+                    "{} attack is on cooldown ({}ms remaining)",
+                    attack_type,
+                    cooldown_ms - elapsed_ms
+                ));
             }
         }
+
+        self.last_attack_at.insert(key, now);
+        Ok(())
     }
 
     /// プレイヤー状態を直接更新（クライアントからのStateUpdate用）
-    pub fn update_state(&mut self, player_id: &str, position: crate::models::Vector3, rotation: crate::models::Vector3) {
-        let character = if player_id == self.player_a_id {
-            &mut self.player_a_character
-        } else if player_id == self.player_b_id {
-            &mut self.player_b_character
+    /// 前回受理した位置からの移動速度がmove_speed（+許容誤差）を超える場合は位置を補正する
+    /// NaN・無限大を含む位置・回転は適用せず、現在の位置に補正する
+    /// 現在の位置自体が有限でない場合は、最後に記録した有限の位置に戻してから検証する
+    pub fn update_state(
+        &mut self,
+        player_id: &str,
+        position: Vector3,
+        rotation: Vector3,
+        now: DateTime<Utc>,
+    ) -> Option<MoveValidation> {
+        let last_update_at = self
+            .last_state_update_at
+            .get(player_id)
+            .copied()
+            .unwrap_or(self.round_started_at);
+        let elapsed_secs = (now
+            .signed_duration_since(last_update_at)
+            .num_milliseconds()
+            .max(0) as f32)
+            / 1000.0;
+        let bounds = self.arena.as_ref().map(|arena| arena.bounds.clone());
+        let slack = self
+            .move_slack
+            .get(player_id)
+            .copied()
+            .unwrap_or(MOVE_DISTANCE_SLACK);
+        let last_finite_position = self.last_finite_position(player_id);
+
+        let character = self.character_mut(player_id)?;
+        if !character.position.is_finite() {
+            character.position = last_finite_position;
+        }
+
+        if !position.is_finite() || !rotation.is_finite() {
+            return Some(MoveValidation::Corrected(character.position.clone()));
+        }
+
+        let max_distance =
+            character.stats.move_speed.max(0) as f32 * MOVE_SPEED_TOLERANCE * elapsed_secs + slack;
+        let moved = distance(&character.position, &position);
+
+        let validation = if !moved.is_finite() {
+            // 移動距離がオーバーフローするほど遠い位置は現在の位置に補正
+            MoveValidation::Corrected(character.position.clone())
+        } else if moved > max_distance {
+            // 許容距離まで移動方向に沿ってクランプ
+            let delta = subtract_vector3(&position, &character.position);
+            let clamped = add_vector3(
+                &character.position,
+                &multiply_vector3(&delta, max_distance / moved),
+            );
+            MoveValidation::Corrected(clamped)
         } else {
-            return; // 不明なプレイヤー
+            MoveValidation::Accepted
+        };
+
+        // アリーナの範囲外への移動は範囲内に補正
+        let validation = match (validation, &bounds) {
+            (MoveValidation::Accepted, Some(bounds)) if !bounds.contains(&position) => {
+                MoveValidation::Corrected(bounds.clamp(&position))
+            }
+            (MoveValidation::Corrected(clamped), Some(bounds)) => {
+                MoveValidation::Corrected(bounds.clamp(&clamped))
+            }
+            (validation, _) => validation,
         };
 
-        character.position = position;
+        character.position = match &validation {
+            MoveValidation::Accepted => position,
+            MoveValidation::Corrected(clamped) => clamped.clone(),
+        };
         character.rotation = rotation;
+        self.last_state_update_at.insert(player_id.to_string(), now);
+        self.move_slack.insert(
+            player_id.to_string(),
+            (max_distance - moved).clamp(0.0, MOVE_DISTANCE_SLACK),
+        );
+        self.record_position(player_id, now);
+
+        Some(validation)
     }
 
-    /// ダメージ適用（クライアントからの報告）
-    pub fn apply_damage(&mut self, player_id: &str, damage: i32) {
-        let character = if player_id == self.player_a_id {
-            &mut self.player_a_character
-        } else if player_id == self.player_b_id {
-            &mut self.player_b_character
-        } else {
+    /// 位置履歴のうち最新の有限の位置（なければラウンド開始時の位置、それもなければ原点）
+    fn last_finite_position(&self, player_id: &str) -> Vector3 {
+        self.position_history
+            .get(player_id)
+            .and_then(|history| {
+                history
+                    .iter()
+                    .rev()
+                    .map(|(_, position)| position)
+                    .find(|position| position.is_finite())
+            })
+            .or_else(|| {
+                self.initial_characters
+                    .get(player_id)
+                    .map(|character| &character.position)
+                    .filter(|position| position.is_finite())
+            })
+            .cloned()
+            .unwrap_or_else(Vector3::zero)
+    }
+
+    /// 現在位置を位置履歴に記録し、保持期間を過ぎた履歴を破棄
+    fn record_position(&mut self, player_id: &str, at: DateTime<Utc>) {
+        let Some(position) = self.character(player_id).map(|c| c.position.clone()) else {
             return;
         };
+        let history = self
+            .position_history
+            .entry(player_id.to_string())
+            .or_default();
+        history.push_back((at, position));
+
+        // 保持期間より前の履歴は、期間開始時点の位置として1件だけ残す
+        let cutoff = at - chrono::Duration::milliseconds(POSITION_HISTORY_MS);
+        while history.len() > 1 && history[1].0 <= cutoff {
+            history.pop_front();
+        }
+    }
+
+    /// 全プレイヤーの初期位置で位置履歴を初期化
+    fn record_initial_positions(&mut self, now: DateTime<Utc>) {
+        self.position_history.clear();
+        for player_id in self.player_ids.clone() {
+            self.record_position(&player_id, now);
+        }
+    }
+
+    /// 指定時刻におけるプレイヤーの位置を位置履歴から取得
+    /// 履歴がない場合は現在位置を返す
+    pub fn position_at(&self, player_id: &str, at: DateTime<Utc>) -> Option<Vector3> {
+        let recorded = self.position_history.get(player_id).and_then(|history| {
+            history
+                .iter()
+                .rev()
+                .find(|(recorded_at, _)| *recorded_at <= at)
+                .map(|(_, position)| position.clone())
+        });
+        recorded.or_else(|| self.character(player_id).map(|c| c.position.clone()))
+    }
+
+    /// 攻撃の命中をサーバー側で判定
+    /// 攻撃者の位置・向き、防御側の位置、attack_range、size_typeのヒットボックスを使用する
+    /// view_timeが指定された場合は、防御側の位置をその時刻まで巻き戻して判定する（最大MAX_REWIND_MS）
+    /// 攻撃者から防御側までの直線経路が障害物に遮られている場合は外れとする
+    /// 相手が複数いる場合は命中した中で最も近い相手を防御側とする
+    /// （命中しなかった場合は障害物に遮られた相手、それもいなければ最も近い相手）
+    /// チーム戦でフレンドリーファイアが無効の場合、味方は対象にならない
+    pub fn judge_attack(
+        &mut self,
+        attacker_id: &str,
+        attack_type: &AttackType,
+        direction: &Vector3,
+        view_time: Option<DateTime<Utc>>,
+        now: DateTime<Utc>,
+    ) -> Option<AttackJudgement> {
+        let rewind_to = view_time.map(|view_time| {
+            view_time.clamp(now - chrono::Duration::milliseconds(MAX_REWIND_MS), now)
+        });
+        let attacker = self.character(attacker_id)?;
+        let judgement = self
+            .target_ids(attacker_id)
+            .into_iter()
+            .filter_map(|defender_id| {
+                let defender = self.character(defender_id).filter(|c| c.is_alive())?;
+                let defender_position = match rewind_to {
+                    Some(at) => self.position_at(defender_id, at)?,
+                    None => defender.position.clone(),
+                };
+                let hitbox_radius = SizeType::from_str(&defender.stats.size_type)
+                    .unwrap_or(SizeType::Medium)
+                    .hitbox_radius();
+
+                let in_range = is_attack_hit(
+                    attack_type,
+                    &attacker.position,
+                    direction,
+                    attacker.stats.attack_range as f32,
+                    &defender_position,
+                    hitbox_radius,
+                );
+                let path_clear = self.is_path_clear(&attacker.position, &defender_position);
+                let judgement = AttackJudgement {
+                    defender_id: defender_id.to_string(),
+                    hit: in_range && path_clear,
+                    blocked: in_range && !path_clear,
+                };
+                Some((distance(&attacker.position, &defender_position), judgement))
+            })
+            // 命中 > 遮られた > それ以外の順に、同じ区分では近い相手を優先
+            .min_by(|(distance_a, a), (distance_b, b)| {
+                (!a.hit, !a.blocked)
+                    .cmp(&(!b.hit, !b.blocked))
+                    .then(distance_a.total_cmp(distance_b))
+            })
+            .map(|(_, judgement)| judgement)?;
+
+        if judgement.hit {
+            self.confirmed_hits
+                .entry((judgement.defender_id.clone(), attack_type.clone()))
+                .or_default()
+                .push_back((attacker_id.to_string(), now));
+        }
+
+        Some(judgement)
+    }
+
+    /// 攻撃者の現在位置から `direction` 方向に弾を発射
+    pub fn spawn_projectile(
+        &mut self,
+        owner_id: &str,
+        direction: &Vector3,
+        now: DateTime<Utc>,
+    ) -> Option<Projectile> {
+        let position = self.character(owner_id)?.position.clone();
+        let projectile = Projectile::spawn(owner_id, position, direction, now)?;
+        self.projectiles.push(projectile.clone());
+        Some(projectile)
+    }
+
+    /// 飛行中の弾を `now` まで進め、命中・障害物への衝突・寿命切れのイベントを返す
+    /// 命中した弾は発射者の`long_range_attack_power`でダメージを適用して消滅する
+    /// 相手までの直線経路が障害物に遮られている場合は命中せず消滅する
+    pub fn update_projectiles(&mut self, now: DateTime<Utc>) -> Vec<ProjectileEvent> {
+        let mut events = Vec::new();
+        let mut remaining = Vec::new();
+
+        for mut projectile in std::mem::take(&mut self.projectiles) {
+            let next_position = projectile.next_position(now);
+            let target_id = self.projectile_target(&projectile, &next_position);
+
+            // 命中する場合は相手まで、しない場合は移動先までの経路を確認
+            let path_end = target_id
+                .as_deref()
+                .and_then(|id| self.character(id))
+                .map_or(next_position.clone(), |target| target.position.clone());
+            let blocked = !self.is_path_clear(&projectile.position, &path_end);
+
+            projectile.position = next_position;
+            projectile.updated_at = now;
+
+            if blocked {
+                events.push(ProjectileEvent::Blocked { projectile });
+                continue;
+            }
+
+            if let Some(target_id) = target_id {
+                if let Some(damage) = self.apply_projectile_damage(&projectile, &target_id) {
+                    events.push(ProjectileEvent::Hit {
+                        projectile,
+                        target_id,
+                        damage,
+                    });
+                    continue;
+                }
+            }
+
+            if projectile.is_expired(now) {
+                events.push(ProjectileEvent::Expired { projectile });
+            } else {
+                remaining.push(projectile);
+            }
+        }
+
+        self.projectiles = remaining;
+        events
+    }
+
+    /// 2点間の直線経路がアリーナの障害物に遮られていないか（アリーナなしの場合は常にtrue）
+    pub fn is_path_clear(&self, from: &Vector3, to: &Vector3) -> bool {
+        self.arena
+            .as_ref()
+            .is_none_or(|arena| arena.is_path_clear(from, to))
+    }
+
+    /// 弾が `next_position` まで移動する間に命中するプレイヤーを取得
+    /// 複数のプレイヤーに当たる場合は弾の現在位置に最も近いプレイヤーとする
+    fn projectile_target(
+        &self,
+        projectile: &Projectile,
+        next_position: &Vector3,
+    ) -> Option<String> {
+        self.target_ids(&projectile.owner_id)
+            .into_iter()
+            .filter_map(|target_id| {
+                let target = self.character(target_id).filter(|c| c.is_alive())?;
+                let hitbox_radius = SizeType::from_str(&target.stats.size_type)
+                    .unwrap_or(SizeType::Medium)
+                    .hitbox_radius();
+
+                is_projectile_hit(
+                    &projectile.position,
+                    next_position,
+                    PROJECTILE_RADIUS,
+                    &target.position,
+                    hitbox_radius,
+                )
+                .then(|| (distance(&projectile.position, &target.position), target_id))
+            })
+            .min_by(|(a, _), (b, _)| a.total_cmp(b))
+            .map(|(_, target_id)| target_id.to_string())
+    }
+
+    /// 弾の命中ダメージを発射者の遠距離攻撃力で計算して適用
+    fn apply_projectile_damage(
+        &mut self,
+        projectile: &Projectile,
+        target_id: &str,
+    ) -> Option<DamageResult> {
+        self.apply_attack_damage(&projectile.owner_id, target_id, &AttackType::Special)
+    }
+
+    /// 被弾報告に対応するサーバーの命中判定があれば古い順に消費し、攻撃者のプレイヤーIDを返す
+    /// 報告期限を過ぎた命中判定は破棄する
+    pub fn take_confirmed_hit(
+        &mut self,
+        defender_id: &str,
+        attack_type: &AttackType,
+        now: DateTime<Utc>,
+    ) -> Option<String> {
+        let hits = self
+            .confirmed_hits
+            .get_mut(&(defender_id.to_string(), attack_type.clone()))?;
+        while let Some((attacker_id, hit_at)) = hits.pop_front() {
+            if now.signed_duration_since(hit_at).num_milliseconds() <= HIT_CONFIRM_WINDOW_MS {
+                return Some(attacker_id);
+            }
+        }
+        None
+    }
+
+    /// 命中判定と一致しない被弾報告を記録し、累計件数を返す
+    pub fn flag_damage_report(&mut self, player_id: &str) -> u32 {
+        let count = self
+            .flagged_damage_reports
+            .entry(player_id.to_string())
+            .or_insert(0);
+        *count += 1;
+        *count
+    }
+
+    /// ダメージ適用
+    /// 適用後のHPを返す（不明なプレイヤーの場合はNone）
+    /// HPが0になったプレイヤーは脱落順に記録する
+    pub fn apply_damage(&mut self, player_id: &str, damage: i32) -> Option<i32> {
+        let character = self.character_mut(player_id)?;
 
         character.hp = (character.hp - damage).max(0);
+        let hp = character.hp;
+        if hp == 0 && !self.eliminated.iter().any(|id| id == player_id) {
+            self.eliminated.push(player_id.to_string());
+        }
+        Some(hp)
+    }
+
+    /// 被弾したプレイヤーへのダメージを攻撃者のステータスからサーバー側で計算して適用
+    /// 攻撃の対象にならない相手（フレンドリーファイア無効時の味方）の場合はNone
+    pub fn apply_attack_damage(
+        &mut self,
+        attacker_id: &str,
+        defender_id: &str,
+        attack_type: &AttackType,
+    ) -> Option<DamageResult> {
+        if !self.is_hostile(attacker_id, defender_id) {
+            return None;
+        }
+        let result = {
+            let attacker = self.character(attacker_id)?;
+            let defender = self.character(defender_id)?;
+            self.damage_calculator
+                .calculate(&attacker.stats, &defender.stats, attack_type)
+        };
+
+        self.apply_damage(defender_id, result.damage)?;
+        Some(result)
+    }
+
+    /// 自分以外の参加プレイヤーのIDを参加順に取得（不明なプレイヤーの場合は空）
+    pub fn opponent_ids(&self, player_id: &str) -> Vec<&str> {
+        if !self.characters.contains_key(player_id) {
+            return Vec::new();
+        }
+        self.player_ids
+            .iter()
+            .map(String::as_str)
+            .filter(|id| *id != player_id)
+            .collect()
+    }
+
+    /// 攻撃の対象になるプレイヤーのID（参加順）
+    pub fn target_ids(&self, attacker_id: &str) -> Vec<&str> {
+        self.opponent_ids(attacker_id)
+            .into_iter()
+            .filter(|defender_id| self.is_hostile(attacker_id, defender_id))
+            .collect()
+    }
+
+    /// `attacker_id`の攻撃が`defender_id`にダメージを与えるか
+    /// 個人戦では自分以外の全員、チーム戦では相手チーム（フレンドリーファイア有効時は味方も含む）
+    pub fn is_hostile(&self, attacker_id: &str, defender_id: &str) -> bool {
+        if attacker_id == defender_id {
+            return false;
+        }
+        match (self.teams.get(attacker_id), self.teams.get(defender_id)) {
+            (Some(a), Some(b)) => self.friendly_fire || a != b,
+            _ => true,
+        }
+    }
+
+    /// チーム戦か
+    pub fn is_team_match(&self) -> bool {
+        !self.teams.is_empty()
+    }
+
+    /// チームの所属プレイヤーID（参加順）
+    pub fn team_members(&self, team: Team) -> Vec<&str> {
+        self.player_ids
+            .iter()
+            .map(String::as_str)
+            .filter(|id| self.teams.get(*id) == Some(&team))
+            .collect()
+    }
+
+    /// 自分以外の参加プレイヤーのキャラクター (player_id -> キャラクター)
+    pub fn opponent_characters(&self, player_id: &str) -> HashMap<String, Character> {
+        self.opponent_ids(player_id)
+            .into_iter()
+            .filter_map(|id| self.characters.get(id).map(|c| (id.to_string(), c.clone())))
+            .collect()
+    }
+
+    /// 参加順で最初の相手のキャラクター（2人対戦向けのフィールド用）
+    pub fn first_opponent_character(&self, player_id: &str) -> Option<&Character> {
+        self.opponent_ids(player_id)
+            .first()
+            .and_then(|id| self.characters.get(*id))
+    }
+
+    /// 参加順の (player_id, キャラクター) 一覧
+    pub fn players(&self) -> Vec<(&str, &Character)> {
+        self.player_ids
+            .iter()
+            .filter_map(|id| self.characters.get(id).map(|c| (id.as_str(), c)))
+            .collect()
+    }
+
+    /// プレイヤーIDからキャラクターを取得
+    pub fn character(&self, player_id: &str) -> Option<&Character> {
+        self.characters.get(player_id)
+    }
+
+    /// プレイヤーIDからキャラクターを可変で取得
+    pub fn character_mut(&mut self, player_id: &str) -> Option<&mut Character> {
+        self.characters.get_mut(player_id)
     }
 
     /// 現在のゲーム状態を取得（デバッグ用）
@@ -86,25 +874,478 @@ impl GameStateManager {
     pub fn get_state(&self) -> GameState {
         GameState {
             matching_id: self.matching_id,
-            player_a: self.player_a_character.clone(),
-            player_b: self.player_b_character.clone(),
+            characters: self.characters.clone(),
             timestamp: Utc::now(),
         }
     }
 
-    /// 勝者を判定
+    /// 現在のラウンドで生存しているプレイヤーID（参加順）
+    pub fn alive_players(&self) -> Vec<&str> {
+        self.players()
+            .into_iter()
+            .filter(|(_, character)| character.is_alive())
+            .map(|(id, _)| id)
+            .collect()
+    }
+
+    /// 降参・切断で脱落していないプレイヤーID（参加順）
+    pub fn active_players(&self) -> Vec<&str> {
+        self.player_ids
+            .iter()
+            .map(String::as_str)
+            .filter(|id| !self.forfeited.iter().any(|forfeited| forfeited == id))
+            .collect()
+    }
+
+    /// 入力・状態更新を受け付けるプレイヤーか（HP0で脱落した、または降参・切断で脱落したプレイヤーは不可）
+    pub fn can_act(&self, player_id: &str) -> bool {
+        self.character(player_id)
+            .is_some_and(|character| character.is_alive())
+            && !self
+                .forfeited
+                .iter()
+                .any(|forfeited| forfeited == player_id)
+    }
+
+    /// 勝者を判定（最後の1人まで生き残ったプレイヤー）
+    /// 全員が同時にHP0になった場合は勝者なし（is_all_knocked_outで判定）
     pub fn check_winner(&self) -> Option<String> {
-        if !self.player_a_character.is_alive() {
-            Some(self.player_b_id.clone())
-        } else if !self.player_b_character.is_alive() {
-            Some(self.player_a_id.clone())
+        match self.alive_players().as_slice() {
+            [winner_id] => Some(winner_id.to_string()),
+            _ => None,
+        }
+    }
+
+    /// 全員がHP0になったか（同時KO）
+    pub fn is_all_knocked_out(&self) -> bool {
+        self.alive_players().is_empty()
+    }
+
+    /// チーム戦で生存者が1チームだけになった場合、そのチームを返す
+    pub fn check_winning_team(&self) -> Option<Team> {
+        let alive = self.alive_players();
+        let team = self.teams.get(*alive.first()?)?;
+        alive
+            .iter()
+            .all(|id| self.teams.get(*id) == Some(team))
+            .then_some(*team)
+    }
+
+    /// 時間切れ時の勝利チームを判定（チーム全員の合計HP割合が高いチーム、同率の場合はNone）
+    pub fn winning_team_by_hp_ratio(&self) -> Option<Team> {
+        let totals = |team: Team| {
+            self.team_members(team)
+                .into_iter()
+                .filter_map(|id| self.character(id))
+                .fold((0i64, 0i64), |(hp, max_hp), c| {
+                    (hp + c.hp as i64, max_hp + c.max_hp.max(1) as i64)
+                })
+        };
+        let ((hp_a, max_hp_a), (hp_b, max_hp_b)) = (totals(Team::A), totals(Team::B));
+        match (hp_a * max_hp_b.max(1)).cmp(&(hp_b * max_hp_a.max(1))) {
+            std::cmp::Ordering::Greater => Some(Team::A),
+            std::cmp::Ordering::Less => Some(Team::B),
+            std::cmp::Ordering::Equal => None,
+        }
+    }
+
+    /// 時間切れ時の勝者を判定（生存者のうちHP割合が最も高いプレイヤー、同率1位がいればNone）
+    pub fn winner_by_hp_ratio(&self) -> Option<String> {
+        let mut ranked: Vec<(&str, &Character)> = self
+            .players()
+            .into_iter()
+            .filter(|(_, character)| character.is_alive())
+            .collect();
+        ranked.sort_by(|(_, a), (_, b)| compare_hp_ratio(b, a));
+
+        match ranked.as_slice() {
+            [(_, first), (_, second), ..] if compare_hp_ratio(first, second).is_eq() => None,
+            [(winner_id, _), ..] => Some(winner_id.to_string()),
+            [] => None,
+        }
+    }
+
+    /// ゲーム開始からの経過時間（秒、一時停止中の時間は含めない）
+    pub fn elapsed_seconds(&self, now: DateTime<Utc>) -> i64 {
+        let now = self.paused_at.map_or(now, |paused_at| paused_at.min(now));
+        now.signed_duration_since(self.started_at)
+            .num_seconds()
+            .max(0)
+    }
+
+    /// 現在のラウンドの残り時間（秒）
+    /// 一時停止中は停止時点の残り時間を返す
+    pub fn remaining_seconds(&self, now: DateTime<Utc>) -> i64 {
+        let now = self.paused_at.map_or(now, |paused_at| paused_at.min(now));
+        let round_elapsed = now
+            .signed_duration_since(self.round_started_at)
+            .num_seconds()
+            .max(0);
+        (self.time_limit_seconds - round_elapsed).max(0)
+    }
+
+    /// 現在のラウンドの試合時間を超過したか
+    pub fn is_time_up(&self, now: DateTime<Utc>) -> bool {
+        self.remaining_seconds(now) == 0
+    }
+
+    /// ラウンド終了判定（生存者が1人以下・全員KO・時間切れ）
+    /// チーム戦では生存者が1チームだけになった時点で決着する
+    /// 決着している場合はラウンド結果を返す
+    pub fn judge_round(&self, now: DateTime<Utc>) -> Option<RoundResult> {
+        let (winner_id, winning_team, end_reason) = if self.is_all_knocked_out() {
+            (None, None, EndReason::KnockOut)
+        } else if self.is_team_match() {
+            if let Some(team) = self.check_winning_team() {
+                (None, Some(team), EndReason::KnockOut)
+            } else if self.is_time_up(now) {
+                (None, self.winning_team_by_hp_ratio(), EndReason::TimeUp)
+            } else {
+                return None;
+            }
+        } else if let Some(winner_id) = self.check_winner() {
+            (Some(winner_id), None, EndReason::KnockOut)
+        } else if self.is_time_up(now) {
+            (self.winner_by_hp_ratio(), None, EndReason::TimeUp)
         } else {
-            None
+            return None;
+        };
+
+        Some(RoundResult {
+            round: self.current_round,
+            winner_id,
+            winning_team,
+            end_reason,
+        })
+    }
+
+    /// 試合に勝つために必要なラウンド勝利数（過半数）
+    pub fn wins_required(&self) -> u32 {
+        self.rounds / 2 + 1
+    }
+
+    /// 試合の勝者を判定
+    /// 過半数を取ったプレイヤー、全ラウンド終了時は勝利数の最も多いプレイヤー（同数1位がいればNone）
+    /// 降参・切断で脱落したプレイヤーは勝者にならない
+    pub fn match_winner(&self) -> Option<String> {
+        let ranked = self
+            .active_players()
+            .into_iter()
+            .map(|id| (id, self.score(id)))
+            .collect();
+        self.decide_match_winner(ranked).map(str::to_string)
+    }
+
+    /// チーム戦の勝利チームを判定（基準はmatch_winnerと同じ、チームの勝利数は所属プレイヤーの勝利数）
+    /// 全員が降参・切断で脱落したチームは勝者にならない
+    pub fn match_winning_team(&self) -> Option<Team> {
+        let active = self.active_players();
+        let ranked = Team::ALL
+            .into_iter()
+            .filter(|team| active.iter().any(|id| self.teams.get(*id) == Some(team)))
+            .map(|team| {
+                let score = self
+                    .team_members(team)
+                    .into_iter()
+                    .map(|id| self.score(id))
+                    .max()
+                    .unwrap_or(0);
+                (team, score)
+            })
+            .collect();
+        self.decide_match_winner(ranked)
+    }
+
+    /// (候補, 勝利数) の一覧から試合の勝者を決める
+    fn decide_match_winner<T: Copy>(&self, mut ranked: Vec<(T, u32)>) -> Option<T> {
+        ranked.sort_by(|(_, a), (_, b)| b.cmp(a));
+
+        match ranked.as_slice() {
+            [(winner, score), ..] if *score >= self.wins_required() => Some(*winner),
+            _ if self.current_round < self.rounds => None,
+            [(_, first), (_, second), ..] if first == second => None,
+            [(winner, _), ..] => Some(*winner),
+            [] => None,
         }
     }
 
-    /// ゲームが終了したかチェック
-    pub fn is_game_over(&self) -> bool {
-        self.check_winner().is_some()
+    /// プレイヤーのラウンド勝利数
+    fn score(&self, player_id: &str) -> u32 {
+        self.scores.get(player_id).copied().unwrap_or(0)
     }
+
+    /// ラウンド結果をスコアに反映し、試合終了か次のラウンドかを決める
+    /// 次のラウンドに進む場合はHP・位置をリセットする
+    pub fn conclude_round(&mut self, round: &RoundResult, now: DateTime<Utc>) -> RoundProgress {
+        if let Some(winner_id) = &round.winner_id {
+            *self.scores.entry(winner_id.clone()).or_insert(0) += 1;
+        }
+        // チーム戦では勝利チームの全員（脱落したプレイヤーを含む）に加算
+        if let Some(team) = round.winning_team {
+            let members: Vec<String> = self
+                .team_members(team)
+                .into_iter()
+                .map(str::to_string)
+                .collect();
+            for player_id in members {
+                *self.scores.entry(player_id).or_insert(0) += 1;
+            }
+        }
+
+        let is_last_round = self.current_round >= self.rounds;
+        if self.is_team_match() {
+            let winning_team = self.match_winning_team();
+            if winning_team.is_some() || is_last_round {
+                return RoundProgress::MatchOver(self.build_team_result(
+                    winning_team,
+                    round.end_reason.clone(),
+                    now,
+                ));
+            }
+        } else {
+            let winner_id = self.match_winner();
+            if winner_id.is_some() || is_last_round {
+                return RoundProgress::MatchOver(self.build_result(
+                    winner_id,
+                    round.end_reason.clone(),
+                    now,
+                ));
+            }
+        }
+
+        self.start_next_round(now);
+        RoundProgress::NextRound
+    }
+
+    /// 次のラウンドを開始（HP・位置・クールダウン等をリセット）
+    /// 降参・切断で脱落したプレイヤーはHP0のまま参加しない
+    fn start_next_round(&mut self, now: DateTime<Utc>) {
+        self.current_round += 1;
+        self.round_started_at = now;
+        self.characters = self.initial_characters.clone();
+        for player_id in &self.forfeited {
+            if let Some(character) = self.characters.get_mut(player_id) {
+                character.hp = 0;
+            }
+        }
+        self.eliminated.clear();
+        self.last_attack_at.clear();
+        self.last_state_update_at.clear();
+        self.move_slack.clear();
+        self.confirmed_hits.clear();
+        self.projectiles.clear();
+        self.record_initial_positions(now);
+    }
+
+    /// 切断による一時停止中か
+    pub fn is_paused(&self) -> bool {
+        self.paused_at.is_some()
+    }
+
+    /// プレイヤーの切断を記録して試合を一時停止し、再接続期限を返す
+    pub fn pause_for_disconnect(
+        &mut self,
+        player_id: &str,
+        now: DateTime<Utc>,
+    ) -> Option<DateTime<Utc>> {
+        if !self.active_players().contains(&player_id) {
+            return None;
+        }
+        let deadline = *self
+            .disconnect_deadlines
+            .entry(player_id.to_string())
+            .or_insert(now + chrono::Duration::seconds(DISCONNECT_GRACE_SECONDS));
+        self.paused_at.get_or_insert(now);
+        Some(deadline)
+    }
+
+    /// プレイヤーの再接続を記録し、全員揃ったら試合を再開する
+    /// 切断中でなかった場合はfalseを返す
+    pub fn resume_player(&mut self, player_id: &str, now: DateTime<Utc>) -> bool {
+        if self.disconnect_deadlines.remove(player_id).is_none() {
+            return false;
+        }
+
+        self.resume_if_all_connected(now);
+        true
+    }
+
+    /// 切断中のプレイヤーがいなくなったら試合を再開する
+    /// 一時停止していた時間は試合時間に含めない
+    fn resume_if_all_connected(&mut self, now: DateTime<Utc>) {
+        if !self.disconnect_deadlines.is_empty() {
+            return;
+        }
+        if let Some(paused_at) = self.paused_at.take() {
+            let paused = now
+                .signed_duration_since(paused_at)
+                .max(chrono::Duration::zero());
+            self.started_at += paused;
+            self.round_started_at += paused;
+            for projectile in &mut self.projectiles {
+                projectile.spawned_at += paused;
+                projectile.expires_at += paused;
+                projectile.updated_at += paused;
+            }
+        }
+    }
+
+    /// 再接続期限を過ぎたプレイヤーを脱落させ、残りが1人以下になれば不戦敗の試合結果を返す
+    /// 全員が期限切れの場合は引き分け。試合が続く場合は残りのプレイヤーで再開する
+    pub fn judge_disconnect(&mut self, now: DateTime<Utc>) -> Option<GameResult> {
+        let mut expired: Vec<String> = self
+            .disconnect_deadlines
+            .iter()
+            .filter(|(_, deadline)| now >= **deadline)
+            .map(|(player_id, _)| player_id.clone())
+            .collect();
+        if expired.is_empty() {
+            return None;
+        }
+
+        // 同時に期限切れになった場合は参加順に脱落させる
+        expired.sort_by_key(|player_id| self.player_ids.iter().position(|id| id == player_id));
+        for player_id in &expired {
+            self.forfeit(player_id);
+        }
+
+        let result = self.judge_forfeit(EndReason::Disconnect, now);
+        if result.is_none() {
+            self.resume_if_all_connected(now);
+        }
+        result
+    }
+
+    /// 降参したプレイヤーを脱落させ、残りが1人以下になれば試合結果を返す
+    /// 2人対戦では残りラウンドに関わらず相手の勝利となる
+    /// 不明なプレイヤー・脱落済みのプレイヤーの場合はNone
+    pub fn surrender(&mut self, player_id: &str, now: DateTime<Utc>) -> Option<GameResult> {
+        if !self.active_players().contains(&player_id) {
+            return None;
+        }
+        self.forfeit(player_id);
+        self.judge_forfeit(EndReason::Surrender, now)
+    }
+
+    /// プレイヤーを脱落させる（HPを0にし、再接続待ちからも外す）
+    fn forfeit(&mut self, player_id: &str) {
+        if self.forfeited.iter().any(|id| id == player_id) {
+            return;
+        }
+        self.forfeited.push(player_id.to_string());
+        self.disconnect_deadlines.remove(player_id);
+        if let Some(character) = self.character_mut(player_id) {
+            character.hp = 0;
+        }
+    }
+
+    /// 脱落していないプレイヤーが1人以下なら試合結果を返す（0人の場合は引き分け）
+    /// チーム戦では脱落していないプレイヤーが1チームだけになればそのチームの勝利
+    fn judge_forfeit(&self, end_reason: EndReason, now: DateTime<Utc>) -> Option<GameResult> {
+        let active = self.active_players();
+        if self.is_team_match() {
+            let mut teams = active.iter().filter_map(|id| self.teams.get(*id));
+            return match teams.next() {
+                None => Some(self.build_team_result(None, end_reason, now)),
+                Some(team) if teams.all(|other| other == team) => {
+                    Some(self.build_team_result(Some(*team), end_reason, now))
+                }
+                _ => None,
+            };
+        }
+
+        match active.as_slice() {
+            [] => Some(self.build_result(None, end_reason, now)),
+            [winner_id] => Some(self.build_result(Some(winner_id.to_string()), end_reason, now)),
+            _ => None,
+        }
+    }
+
+    /// 最終順位（1位が先頭）
+    /// 勝者（チーム戦では勝利チームの全員） > 脱落していない > 脱落が遅い、の順に並べ、同じ区分では
+    /// ラウンド勝利数 > 現在のラウンドで生き残った・HP0になるのが遅い > HP割合の順に並べる
+    pub fn placements(&self, winner_id: Option<&str>, winning_team: Option<Team>) -> Vec<String> {
+        let standing = |player_id: &str| {
+            let forfeited_at = self.forfeited.iter().position(|id| id == player_id);
+            let eliminated_at = self.eliminated.iter().position(|id| id == player_id);
+            let is_winner = winner_id == Some(player_id)
+                || winning_team.is_some_and(|team| self.teams.get(player_id) == Some(&team));
+            (
+                is_winner,
+                forfeited_at.unwrap_or(usize::MAX),
+                self.scores.get(player_id).copied().unwrap_or(0),
+                eliminated_at.unwrap_or(usize::MAX),
+            )
+        };
+
+        let mut placements = self.player_ids.clone();
+        placements.sort_by(|a, b| {
+            standing(b).cmp(&standing(a)).then_with(|| {
+                match (self.character(a), self.character(b)) {
+                    (Some(a), Some(b)) => compare_hp_ratio(b, a),
+                    _ => std::cmp::Ordering::Equal,
+                }
+            })
+        });
+        placements
+    }
+
+    /// 勝者（引き分けの場合はNone）と終了理由からゲーム結果を生成
+    pub fn build_result(
+        &self,
+        winner_id: Option<String>,
+        end_reason: EndReason,
+        now: DateTime<Utc>,
+    ) -> GameResult {
+        self.result(winner_id, None, end_reason, now)
+    }
+
+    /// チーム戦の勝利チーム（引き分けの場合はNone）と終了理由からゲーム結果を生成
+    pub fn build_team_result(
+        &self,
+        winning_team: Option<Team>,
+        end_reason: EndReason,
+        now: DateTime<Utc>,
+    ) -> GameResult {
+        self.result(None, winning_team, end_reason, now)
+    }
+
+    fn result(
+        &self,
+        winner_id: Option<String>,
+        winning_team: Option<Team>,
+        end_reason: EndReason,
+        now: DateTime<Utc>,
+    ) -> GameResult {
+        let placements = self.placements(winner_id.as_deref(), winning_team);
+        let has_winner = winner_id.is_some() || winning_team.is_some();
+        let loser_id = has_winner.then(|| placements.last().cloned()).flatten();
+        let outcome = if has_winner {
+            GameOutcome::Win
+        } else {
+            GameOutcome::Draw
+        };
+
+        GameResult {
+            matching_id: self.matching_id,
+            game_id: self.game_id,
+            outcome,
+            winner_id,
+            winning_team,
+            loser_id,
+            player_ids: self.player_ids.clone(),
+            placements,
+            end_reason,
+            scores: self.scores.clone(),
+            play_time_seconds: self.elapsed_seconds(now),
+            finished_at: now,
+        }
+    }
+}
+
+/// HP割合（hp / max_hp）を整数演算で比較
+fn compare_hp_ratio(a: &Character, b: &Character) -> std::cmp::Ordering {
+    let ratio_a = a.hp as i64 * b.max_hp.max(1) as i64;
+    let ratio_b = b.hp as i64 * a.max_hp.max(1) as i64;
+    ratio_a.cmp(&ratio_b)
 }
@@ -0,0 +1,163 @@
+use crate::models::Vector3;
+use crate::utils::{
+    add_vector3, distance, dot_vector3, multiply_vector3, normalize_vector3, subtract_vector3,
+    vector3_length,
+};
+
+/// 長さがゼロとみなす閾値
+const EPSILON: f32 = 1e-6;
+
+/// 始点・方向・長さを持つレイ（線分）
+#[derive(Debug, Clone)]
+pub struct Ray {
+    pub origin: Vector3,
+    /// 正規化済みの方向
+    pub direction: Vector3,
+    pub length: f32,
+}
+
+impl Ray {
+    /// 2点を結ぶレイを作成
+    pub fn between(from: &Vector3, to: &Vector3) -> Self {
+        let delta = subtract_vector3(to, from);
+        Self {
+            origin: from.clone(),
+            direction: normalize_vector3(&delta),
+            length: vector3_length(&delta),
+        }
+    }
+
+    /// レイの終点
+    pub fn end(&self) -> Vector3 {
+        add_vector3(
+            &self.origin,
+            &multiply_vector3(&self.direction, self.length),
+        )
+    }
+}
+
+/// 軸平行バウンディングボックス
+#[derive(Debug, Clone)]
+pub struct Aabb {
+    pub min: Vector3,
+    pub max: Vector3,
+}
+
+impl Aabb {
+    /// レイとの交差判定（スラブ法）
+    /// 交差する場合はレイの始点から最初に接触するまでの距離を返す（始点が内部なら0）
+    pub fn raycast(&self, ray: &Ray) -> Option<f32> {
+        let axes = [
+            (ray.origin.x, ray.direction.x, self.min.x, self.max.x),
+            (ray.origin.y, ray.direction.y, self.min.y, self.max.y),
+            (ray.origin.z, ray.direction.z, self.min.z, self.max.z),
+        ];
+
+        let mut t_enter = 0.0_f32;
+        let mut t_exit = ray.length;
+        for (origin, direction, min, max) in axes {
+            if direction.abs() < EPSILON {
+                // この軸に平行なレイはスラブの外なら交差しない
+                if origin < min || origin > max {
+                    return None;
+                }
+                continue;
+            }
+            let t1 = (min - origin) / direction;
+            let t2 = (max - origin) / direction;
+            t_enter = t_enter.max(t1.min(t2));
+            t_exit = t_exit.min(t1.max(t2));
+            if t_enter > t_exit {
+                return None;
+            }
+        }
+        Some(t_enter)
+    }
+}
+
+/// カプセル（線分 `start`-`end` から半径 `radius` 以内の領域）
+#[derive(Debug, Clone)]
+pub struct Capsule {
+    pub start: Vector3,
+    pub end: Vector3,
+    pub radius: f32,
+}
+
+impl Capsule {
+    /// レイとの交差判定（レイと軸の最短距離が半径以下なら交差）
+    pub fn intersects(&self, ray: &Ray) -> bool {
+        segment_distance(&ray.origin, &ray.end(), &self.start, &self.end) <= self.radius
+    }
+}
+
+/// 衝突判定用の形状
+#[derive(Debug, Clone)]
+pub enum Shape {
+    Aabb(Aabb),
+    Capsule(Capsule),
+}
+
+impl Shape {
+    /// レイが形状に触れるか
+    pub fn intersects(&self, ray: &Ray) -> bool {
+        match self {
+            Shape::Aabb(aabb) => aabb.raycast(ray).is_some(),
+            Shape::Capsule(capsule) => capsule.intersects(ray),
+        }
+    }
+}
+
+/// 線分 `start`-`end` 上で `point` に最も近い点
+pub fn closest_point_on_segment(start: &Vector3, end: &Vector3, point: &Vector3) -> Vector3 {
+    let segment = subtract_vector3(end, start);
+    let length_sq = dot_vector3(&segment, &segment);
+    if length_sq < EPSILON {
+        return start.clone();
+    }
+    let t = (dot_vector3(&subtract_vector3(point, start), &segment) / length_sq).clamp(0.0, 1.0);
+    add_vector3(start, &multiply_vector3(&segment, t))
+}
+
+/// 2つの線分 `p1`-`q1` と `p2`-`q2` の最短距離
+pub fn segment_distance(p1: &Vector3, q1: &Vector3, p2: &Vector3, q2: &Vector3) -> f32 {
+    let d1 = subtract_vector3(q1, p1);
+    let d2 = subtract_vector3(q2, p2);
+    let r = subtract_vector3(p1, p2);
+    let a = dot_vector3(&d1, &d1);
+    let e = dot_vector3(&d2, &d2);
+    let f = dot_vector3(&d2, &r);
+
+    // 各線分上の最近点のパラメータ (s, t) を求める
+    let (s, t) = if a < EPSILON && e < EPSILON {
+        (0.0, 0.0)
+    } else if a < EPSILON {
+        (0.0, (f / e).clamp(0.0, 1.0))
+    } else {
+        let c = dot_vector3(&d1, &r);
+        if e < EPSILON {
+            ((-c / a).clamp(0.0, 1.0), 0.0)
+        } else {
+            let b = dot_vector3(&d1, &d2);
+            let denom = a * e - b * b;
+            // 平行な場合は始点を仮に選ぶ
+            let s = if denom.abs() > EPSILON {
+                ((b * f - c * e) / denom).clamp(0.0, 1.0)
+            } else {
+                0.0
+            };
+            let t = (b * s + f) / e;
+            if t < 0.0 {
+                ((-c / a).clamp(0.0, 1.0), 0.0)
+            } else if t > 1.0 {
+                (((b - c) / a).clamp(0.0, 1.0), 1.0)
+            } else {
+                (s, t)
+            }
+        }
+    };
+
+    distance(
+        &add_vector3(p1, &multiply_vector3(&d1, s)),
+        &add_vector3(p2, &multiply_vector3(&d2, t)),
+    )
+}
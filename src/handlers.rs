@@ -1,10 +1,17 @@
 pub mod model_upload;
+pub mod replay;
 pub mod websocket;
 
 pub use model_upload::{list_models, upload_model};
+pub use replay::get_replay;
 pub use websocket::ws_handler;
 
+use crate::game::arena::ArenaRegistry;
+use crate::game::manager::GameManager;
+use crate::game::replay::ReplayStore;
 use crate::models::{MatchingSession, WsMessage};
+use actix::Addr;
+use sqlx::SqlitePool;
 use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
 use tokio::sync::mpsc;
@@ -23,3 +30,22 @@ pub type LobbyPlayers = Arc<Mutex<HashMap<String, (mpsc::UnboundedSender<WsMessa
 
 /// 共有マッチングセッション管理
 pub type MatchingSessions = Arc<Mutex<HashMap<Uuid, MatchingSession>>>;
+
+/// 読み込み済みのアリーナ定義（起動時に読み込み、以降は読み取りのみ）
+pub type Arenas = Arc<ArenaRegistry>;
+
+/// リプレイの保存先
+pub type Replays = Arc<ReplayStore>;
+
+/// WebSocketセッションが共有するサーバー状態
+#[derive(Clone)]
+pub struct AppState {
+    pub sessions: MatchingSessions,
+    pub ws_channels: WsChannels,
+    pub waiting_players: WaitingPlayers,
+    pub lobby_players: LobbyPlayers,
+    pub game_manager: Addr<GameManager>,
+    pub db_pool: SqlitePool,
+    pub arenas: Arenas,
+    pub replays: Replays,
+}
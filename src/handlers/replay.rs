@@ -0,0 +1,34 @@
+use crate::handlers::Replays;
+use actix_web::{HttpResponse, Responder, web};
+use uuid::Uuid;
+
+/// GET /api/replays/{game_id} - リプレイのダウンロード
+pub async fn get_replay(path: web::Path<Uuid>, replays: web::Data<Replays>) -> impl Responder {
+    let game_id = path.into_inner();
+    println!("📥 GET /api/replays/{}", game_id);
+
+    match replays.load(&game_id).await {
+        Ok(Some(replay)) => {
+            println!(
+                "✅ Found replay: game_id={}, events={}",
+                game_id,
+                replay.events.len()
+            );
+            HttpResponse::Ok()
+                .insert_header((
+                    "Content-Disposition",
+                    format!("attachment; filename=\"replay-{}.json\"", game_id),
+                ))
+                .json(replay)
+        }
+        Ok(None) => HttpResponse::NotFound().json(serde_json::json!({
+            "error": "Replay not found"
+        })),
+        Err(e) => {
+            println!("❌ Failed to load replay {}: {}", game_id, e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to load replay"
+            }))
+        }
+    }
+}
@@ -1,11 +1,20 @@
 use crate::db::models::Model3D;
-use crate::game::manager::{GameManager, ProcessInput, StartGame};
+use crate::game::bot::{BotBrain, BotPlayer};
+use crate::game::manager::{AddSpectator, GameManager, ProcessInput, RemoveSpectator, StartGame};
+use crate::game::replay::{
+    DEFAULT_REPLAY_SPEED, REPLAY_FRAME_INTERVAL_MS, Replay, ReplayPlayback, validate_replay_speed,
+};
 use crate::game::state::GameStateManager;
-use crate::handlers::{LobbyPlayers, MatchingSessions, WaitingPlayers, WsChannels};
-use crate::models::{MatchingStatus, WsMessage};
+use crate::handlers::{
+    AppState, Arenas, LobbyPlayers, MatchingSessions, Replays, WaitingPlayers, WsChannels,
+};
+use crate::models::{
+    BotDifficulty, DEFAULT_TEAM_MAX_PLAYERS, MatchSettings, MatchingStatus, Team, WsMessage,
+};
 use actix::prelude::*;
 use actix_web::{web, Error, HttpRequest, HttpResponse};
 use actix_web_actors::ws;
+use rand::seq::IndexedRandom;
 use sqlx::SqlitePool;
 use std::time::{Duration, Instant};
 use tokio::sync::mpsc;
@@ -31,37 +40,48 @@ pub struct WsSession {
     game_manager: Addr<GameManager>,
     /// データベースプール
     db_pool: SqlitePool,
+    /// アリーナ定義
+    arenas: Arenas,
+    /// リプレイの保存先
+    replays: Replays,
     /// メッセージ受信チャンネル
     rx: Option<mpsc::UnboundedReceiver<WsMessage>>,
     /// メッセージ送信チャンネル
     tx: mpsc::UnboundedSender<WsMessage>,
     /// セッションID (再接続時の競合防止用)
     session_id: Uuid,
+    /// 観戦中のマッチングID（観戦者からの入力はすべて拒否する）
+    spectating: Option<Uuid>,
+    /// リプレイ再生モードか（リプレイの操作以外の入力はすべて拒否する）
+    replay_mode: bool,
+    /// 再生中のリプレイ
+    replay: Option<ReplayPlayback>,
+    /// リプレイ再生タイマー
+    replay_handle: Option<SpawnHandle>,
 }
 
 impl WsSession {
-    pub fn new(
-        sessions: MatchingSessions,
-        ws_channels: WsChannels,
-        waiting_players: WaitingPlayers,
-        lobby_players: LobbyPlayers,
-        game_manager: Addr<GameManager>,
-        db_pool: SqlitePool,
-    ) -> Self {
+    pub fn new(state: AppState) -> Self {
         let (tx, rx) = mpsc::unbounded_channel();
         Self {
             hb: Instant::now(),
             player_id: None,
             matching_id: None,
-            sessions,
-            ws_channels,
-            waiting_players,
-            lobby_players,
-            game_manager,
-            db_pool,
+            sessions: state.sessions,
+            ws_channels: state.ws_channels,
+            waiting_players: state.waiting_players,
+            lobby_players: state.lobby_players,
+            game_manager: state.game_manager,
+            db_pool: state.db_pool,
+            arenas: state.arenas,
+            replays: state.replays,
             rx: Some(rx),
             tx,
             session_id: Uuid::new_v4(),
+            spectating: None,
+            replay_mode: false,
+            replay: None,
+            replay_handle: None,
         }
     }
 
@@ -97,18 +117,55 @@ impl WsSession {
     fn handle_create_matching(
         &mut self,
         username: Option<String>,
-        _ctx: &mut ws::WebsocketContext<Self>,
+        settings: MatchSettings,
+        ctx: &mut ws::WebsocketContext<Self>,
     ) {
         let Some(player_id) = &self.player_id else {
             println!("❌ handle_create_matching: player_id is None");
             return;
         };
 
+        if let Err(message) = settings.validate() {
+            println!("❌ Invalid match settings: {}", message);
+            let _ = self.tx.send(WsMessage::Error { message });
+            return;
+        }
+        let Some(arena) = self.arenas.get(&settings.arena_id) else {
+            println!("❌ Unknown arena: {}", settings.arena_id);
+            let _ = self.tx.send(WsMessage::Error {
+                message: format!(
+                    "Unknown arena: {} (available: {})",
+                    settings.arena_id,
+                    self.arenas.ids().join(", ")
+                ),
+            });
+            return;
+        };
+        if !arena.has_spawn_points_for(settings.max_players) {
+            println!(
+                "❌ Not enough spawn points: arena_id={}, max_players={}",
+                settings.arena_id, settings.max_players
+            );
+            let _ = self.tx.send(WsMessage::Error {
+                message: format!(
+                    "Arena {} does not support {} players",
+                    settings.arena_id, settings.max_players
+                ),
+            });
+            return;
+        }
+
         println!(
             "🎯 handle_create_matching: player_id={}, username={:?}",
             player_id, username
         );
 
+        // ボット対戦は待機リストを経由せずに即座にマッチングを成立させる
+        if let Some(difficulty) = settings.bot_difficulty {
+            self.handle_create_bot_matching(username, settings, difficulty, ctx);
+            return;
+        }
+
         let player_id_clone = player_id.clone();
         let sessions = self.sessions.clone();
         let waiting_players = self.waiting_players.clone();
@@ -116,10 +173,15 @@ impl WsSession {
         let tx = self.tx.clone();
 
         // マッチングセッションを作成
-        let session = crate::models::MatchingSession::new_with_username(
+        let mut session = crate::models::MatchingSession::new_with_username(
             player_id_clone.clone(),
             username.clone(),
         );
+        session.settings = settings;
+        // チーム戦では作成者はチームAに所属
+        if session.settings.team_mode {
+            session.players[0].team = Some(Team::A);
+        }
         let matching_id = session.matching_id;
         self.matching_id = Some(matching_id);
 
@@ -148,12 +210,7 @@ impl WsSession {
             .filter_map(|(_, (mid, _, _))| {
                 sessions_lock
                     .get(mid)
-                    .map(|session| crate::models::MatchingInfo {
-                        matching_id: *mid,
-                        creator_username: session.creator_username.clone(),
-                        created_at: session.created_at,
-                        status: session.status.clone(),
-                    })
+                    .map(crate::models::MatchingInfo::from_session)
             })
             .collect();
         drop(sessions_lock);
@@ -177,6 +234,130 @@ impl WsSession {
         self.broadcast_update_matchings();
     }
 
+    /// ボット対戦のマッチング作成処理
+    /// 未使用のモンスターからボットのキャラクターを選び、ボットを2人目の参加者として参加させる
+    fn handle_create_bot_matching(
+        &mut self,
+        username: Option<String>,
+        settings: MatchSettings,
+        difficulty: BotDifficulty,
+        ctx: &mut ws::WebsocketContext<Self>,
+    ) {
+        let Some(player_id) = self.player_id.clone() else {
+            println!("❌ handle_create_bot_matching: player_id is None");
+            return;
+        };
+
+        let db_pool = self.db_pool.clone();
+        let sessions = self.sessions.clone();
+        let ws_channels = self.ws_channels.clone();
+        let lobby_players = self.lobby_players.clone();
+        let game_manager = self.game_manager.clone();
+        let tx = self.tx.clone();
+        let session_id = self.session_id;
+
+        // ロビー待機リストから削除
+        lobby_players.lock().unwrap().remove(&player_id);
+
+        ctx.spawn(
+            async move {
+                let monsters = match Model3D::list_unused(&db_pool).await {
+                    Ok(monsters) => monsters,
+                    Err(e) => {
+                        println!("❌ Failed to list unused monsters: {}", e);
+                        let _ = tx.send(WsMessage::Error {
+                            message: "Failed to prepare the bot".to_string(),
+                        });
+                        return None;
+                    }
+                };
+                let Some(monster) = monsters.choose(&mut rand::rng()).cloned() else {
+                    println!("❌ No monster available for the bot");
+                    let _ = tx.send(WsMessage::Error {
+                        message: "No monster available for the bot".to_string(),
+                    });
+                    return None;
+                };
+                // 人間の選択と同じく、ボットが選んだモンスターも使用済みにする
+                if let Err(e) = Model3D::mark_as_used(&db_pool, &monster.id).await {
+                    println!("❌ Failed to mark bot monster as used: {}", e);
+                    let _ = tx.send(WsMessage::Error {
+                        message: "Failed to prepare the bot".to_string(),
+                    });
+                    return None;
+                }
+
+                // ボットは選択済み・準備完了の状態で参加する
+                let monster_stats = crate::models::MonsterStats::from_monster(&monster);
+                let character =
+                    crate::models::Character::new(monster.id.clone(), monster_stats.clone());
+                let bot_id = format!("bot-{}", Uuid::new_v4());
+                let mut bot = crate::models::Player::new(bot_id.clone());
+                bot.selected_model_id = Some(monster.id.clone());
+                bot.character = Some(character.clone());
+                bot.ready = true;
+
+                let mut session =
+                    crate::models::MatchingSession::new_with_username(player_id.clone(), username);
+                session.settings = settings;
+                session.players.push(bot);
+                session.status = MatchingStatus::Matched;
+                let matching_id = session.matching_id;
+                sessions.lock().unwrap().insert(matching_id, session);
+
+                // ボットには人間と同じくWsMessageを受け取るチャンネルを割り当てる
+                let (bot_tx, bot_rx) = mpsc::unbounded_channel();
+                {
+                    let mut channels = ws_channels.lock().unwrap();
+                    let player_map = channels.entry(matching_id).or_default();
+                    player_map.insert(player_id.clone(), (tx.clone(), session_id));
+                    player_map.insert(bot_id.clone(), (bot_tx, Uuid::new_v4()));
+                }
+
+                BotPlayer::new(
+                    matching_id,
+                    BotBrain::new(bot_id.clone(), difficulty),
+                    bot_rx,
+                    game_manager,
+                    sessions,
+                )
+                .start();
+
+                println!(
+                    "🤖 Bot matching created: matching_id={}, bot_id={}, monster={}, difficulty={:?}",
+                    matching_id, bot_id, monster.id, difficulty
+                );
+
+                let now = chrono::Utc::now();
+                let _ = tx.send(WsMessage::MatchingCreated {
+                    matching_id,
+                    current_matchings: Vec::new(),
+                    timestamp: now,
+                });
+                let _ = tx.send(WsMessage::MatchingEstablished {
+                    matching_id,
+                    opponent_id: bot_id.clone(),
+                    player_ids: vec![player_id, bot_id.clone()],
+                    timestamp: now,
+                });
+                let _ = tx.send(WsMessage::OpponentCharacterSelected {
+                    player_id: bot_id,
+                    character,
+                    monster_stats: Some(monster_stats),
+                    timestamp: now,
+                });
+                Some(matching_id)
+            }
+            .into_actor(self)
+            .map(|matching_id, act, _ctx| {
+                if let Some(matching_id) = matching_id {
+                    act.matching_id = Some(matching_id);
+                }
+                act.broadcast_update_matchings();
+            }),
+        );
+    }
+
     /// UpdateMatchingsをブロードキャスト
     fn broadcast_update_matchings(&self) {
         let waiting_players = self.waiting_players.lock().unwrap();
@@ -196,12 +377,7 @@ impl WsSession {
                 .filter_map(|(_, (mid, _, _))| {
                     sessions
                         .get(mid)
-                        .map(|session| crate::models::MatchingInfo {
-                            matching_id: *mid,
-                            creator_username: session.creator_username.clone(),
-                            created_at: session.created_at,
-                            status: session.status.clone(),
-                        })
+                        .map(crate::models::MatchingInfo::from_session)
                 })
                 .collect();
 
@@ -220,12 +396,7 @@ impl WsSession {
                 .filter_map(|(_, (mid, _, _))| {
                     sessions
                         .get(mid)
-                        .map(|session| crate::models::MatchingInfo {
-                            matching_id: *mid,
-                            creator_username: session.creator_username.clone(),
-                            created_at: session.created_at,
-                            status: session.status.clone(),
-                        })
+                        .map(crate::models::MatchingInfo::from_session)
                 })
                 .collect();
 
@@ -238,7 +409,12 @@ impl WsSession {
     }
 
     /// マッチング参加処理
-    fn handle_join_match(&mut self, matching_id: Uuid, _ctx: &mut ws::WebsocketContext<Self>) {
+    fn handle_join_match(
+        &mut self,
+        matching_id: Uuid,
+        team: Option<Team>,
+        _ctx: &mut ws::WebsocketContext<Self>,
+    ) {
         let Some(player_id) = &self.player_id else {
             println!("❌ handle_join_match: player_id is None");
             return;
@@ -285,9 +461,9 @@ impl WsSession {
             return;
         }
 
-        // 同じプレイヤーIDチェック
-        if session.player_a.id == player_id_clone {
-            println!("❌ Cannot join your own matching session");
+        // 参加済みチェック（作成者を含む）
+        if session.is_participant(&player_id_clone) {
+            println!("❌ Cannot join a matching session you are already in");
             let error_msg = crate::models::WsMessage::Error {
                 message: "Cannot join your own matching session".to_string(),
             };
@@ -295,20 +471,55 @@ impl WsSession {
             return;
         }
 
-        // プレイヤーBを設定してマッチング成立
-        let player_a_id = session.player_a.id.clone();
-        session.player_b = Some(crate::models::Player::new(player_id_clone.clone()));
-        session.status = crate::models::MatchingStatus::Matched;
+        // 満員チェック
+        if session.is_full() {
+            println!("❌ Matching session is full: matching_id={}", matching_id);
+            let error_msg = crate::models::WsMessage::Error {
+                message: "This matching session is full".to_string(),
+            };
+            let _ = tx.send(error_msg);
+            return;
+        }
+
+        // チーム戦ではチームを割り当てる（希望したチームが満員ならエラー）
+        let team = match session.assign_team(team) {
+            Ok(team) => team,
+            Err(message) => {
+                println!("❌ {}", message);
+                let _ = tx.send(crate::models::WsMessage::Error { message });
+                return;
+            }
+        };
+
+        // 参加者に追加し、参加人数に達したらマッチング成立
+        let creator_id = session.creator_id().to_string();
+        let mut player = crate::models::Player::new(player_id_clone.clone());
+        player.team = team;
+        session.players.push(player);
+        let is_full = session.is_full();
+        if is_full {
+            session.status = crate::models::MatchingStatus::Matched;
+        }
+        let player_ids: Vec<String> = session.players.iter().map(|p| p.id.clone()).collect();
+        let max_players = session.settings.max_players;
         drop(sessions_lock);
 
         println!(
-            "✅ Matching successful: player_a={}, player_b={}",
-            player_a_id, player_id_clone
+            "✅ Player joined: matching_id={}, player_id={}, team={:?}, players={}/{}",
+            matching_id,
+            player_id_clone,
+            team,
+            player_ids.len(),
+            max_players
         );
 
-        // 待機リストから両者を削除
+        // 作成者のsenderを取得（成立時は待機リストから削除）
         let mut waiting_players_lock = waiting_players.lock().unwrap();
-        let player_a_sender = waiting_players_lock.remove(&player_a_id);
+        let creator_sender = if is_full {
+            waiting_players_lock.remove(&creator_id)
+        } else {
+            waiting_players_lock.get(&creator_id).cloned()
+        };
         waiting_players_lock.remove(&player_id_clone);
         drop(waiting_players_lock);
 
@@ -317,32 +528,32 @@ impl WsSession {
         lobby_players_lock.remove(&player_id_clone);
         drop(lobby_players_lock);
 
-        // WsChannelsに両者を登録
+        // WsChannelsに登録
         let mut channels = ws_channels.lock().unwrap();
         let player_map = channels.entry(matching_id).or_default();
 
-        // プレイヤーAのsenderを登録（waiting_playersにいた場合）
-        if let Some(sender) = player_a_sender {
+        // 作成者のsenderを登録（waiting_playersにいた場合）
+        if let Some(sender) = creator_sender {
             println!(
-                "✅ Registering player_a sender from waiting_players: {}",
-                player_a_id
+                "✅ Registering creator sender from waiting_players: {}",
+                creator_id
             );
-            player_map.insert(player_a_id.clone(), (sender.1, sender.2));
-        } else {
+            player_map.insert(creator_id.clone(), (sender.1, sender.2));
+        } else if !player_map.contains_key(&creator_id) {
             // waiting_playersにいない場合は、既にws_channelsに接続している可能性
-            println!("⚠️ player_a not found in waiting_players: {}", player_a_id);
+            println!("⚠️ creator not found in waiting_players: {}", creator_id);
             println!(
                 "📋 Current player_map keys before registration: {:?}",
                 player_map.keys().collect::<Vec<_>>()
             );
         }
 
-        // プレイヤーBのsenderを登録
-        println!("✅ Registering player_b sender: {}", player_id_clone);
+        // 参加したプレイヤーのsenderを登録
+        println!("✅ Registering joined player sender: {}", player_id_clone);
         player_map.insert(player_id_clone.clone(), (tx.clone(), self.session_id));
         drop(channels);
 
-        // 両者にMatchingEstablishedを送信（モデルデータはまだNone）
+        // 参加者全員に通知（揃った場合はMatchingEstablished、モデルデータはまだNone）
         let channels = ws_channels.lock().unwrap();
         if let Some(player_map) = channels.get(&matching_id) {
             println!(
@@ -350,36 +561,34 @@ impl WsSession {
                 player_map.keys().collect::<Vec<_>>()
             );
 
-            // プレイヤーAに送信
-            if let Some((sender_a, _)) = player_map.get(&player_a_id) {
-                let msg = crate::models::WsMessage::MatchingEstablished {
-                    matching_id,
-                    opponent_id: player_id_clone.clone(),
-                    timestamp: chrono::Utc::now(),
+            let now = chrono::Utc::now();
+            for player_id in &player_ids {
+                let Some((sender, _)) = player_map.get(player_id) else {
+                    println!("❌ sender not found for player_id: {}", player_id);
+                    continue;
                 };
-                println!(
-                    "✅ Sending MatchingEstablished to player_a: {}",
-                    player_a_id
-                );
-                let _ = sender_a.send(msg);
-            } else {
-                println!("❌ sender_a not found for player_a_id: {}", player_a_id);
-            }
-
-            // プレイヤーBに送信
-            if let Some((sender_b, _)) = player_map.get(&player_id_clone) {
-                let msg = crate::models::WsMessage::MatchingEstablished {
-                    matching_id,
-                    opponent_id: player_a_id.clone(),
-                    timestamp: chrono::Utc::now(),
+                let msg = if is_full {
+                    let Some(opponent_id) = player_ids.iter().find(|id| *id != player_id) else {
+                        continue;
+                    };
+                    println!("✅ Sending MatchingEstablished to: {}", player_id);
+                    crate::models::WsMessage::MatchingEstablished {
+                        matching_id,
+                        opponent_id: opponent_id.clone(),
+                        player_ids: player_ids.clone(),
+                        timestamp: now,
+                    }
+                } else {
+                    crate::models::WsMessage::PlayerJoined {
+                        matching_id,
+                        player_id: player_id_clone.clone(),
+                        team,
+                        player_count: player_ids.len(),
+                        max_players,
+                        timestamp: now,
+                    }
                 };
-                println!(
-                    "✅ Sending MatchingEstablished to player_b: {}",
-                    player_id_clone
-                );
-                let _ = sender_b.send(msg);
-            } else {
-                println!("❌ sender_b not found for player_b_id: {}", player_id_clone);
+                let _ = sender.send(msg);
             }
         } else {
             println!("❌ player_map not found for matching_id: {}", matching_id);
@@ -405,6 +614,16 @@ impl WsSession {
             player_id, matching_id, model_id
         );
 
+        // 再戦時は前回のバトルで自分が使用したモデルを再選択できる
+        let is_previous_model = {
+            let sessions = self.sessions.lock().unwrap();
+            sessions.get(matching_id).is_some_and(|session| {
+                session
+                    .player(player_id)
+                    .is_some_and(|p| p.previous_model_id.as_deref() == Some(&model_id))
+            })
+        };
+
         // モデルIDの検証（非同期）
         let db_pool = self.db_pool.clone();
         let model_id_clone = model_id.clone();
@@ -414,12 +633,13 @@ impl WsSession {
         let player_id_clone = player_id.clone();
         let matching_id_clone = *matching_id; // Copy Uuid
         let game_manager = self.game_manager.clone();
+        let arenas = self.arenas.clone();
 
         ctx.spawn(
             async move {
                 match Model3D::find_by_id(&db_pool, &model_id_clone).await {
                     Ok(Some(model)) => {
-                        if model.is_used {
+                        if model.is_used && !is_previous_model {
                             println!("❌ Model ID already used: {}", model_id_clone);
                             let error_msg = WsMessage::Error {
                                 message: format!(
@@ -445,110 +665,92 @@ impl WsSession {
                         // ここからセッション更新処理
                         let mut sessions_lock = sessions.lock().unwrap();
                         if let Some(session) = sessions_lock.get_mut(&matching_id_clone) {
-                            let character = crate::models::Character::new(model_id_clone.clone());
-
-                            // プレイヤーA or Bの判定と相手IDの取得
-                            let opponent_id = if session.player_a.id == player_id_clone {
-                                println!("📌 Player is player_a, setting character and ready");
-                                session.player_a.character = Some(character.clone());
-                                session.player_a.ready = true;
-                                session.player_b.as_ref().map(|p| p.id.clone())
-                            } else if let Some(ref mut player_b) = session.player_b {
-                                if player_b.id == player_id_clone {
-                                    println!("📌 Player is player_b, setting character and ready");
-                                    player_b.character = Some(character.clone());
-                                    player_b.ready = true;
-                                    Some(session.player_a.id.clone())
-                                } else {
-                                    println!("❌ Player ID mismatch");
-                                    None
-                                }
-                            } else {
-                                println!("❌ player_b is None");
-                                None
-                            };
-
-                            println!("🎯 opponent_id: {:?}", opponent_id);
+                            // モンスターのステータスでキャラクターを初期化
+                            let monster_stats = crate::models::MonsterStats::from_monster(&model);
+                            let character = crate::models::Character::new(
+                                model_id_clone.clone(),
+                                monster_stats.clone(),
+                            );
 
-                            // 相手に通知（モンスターステータスを含む）
-                            if let Some(opponent_id) = opponent_id {
-                                let monster_stats = crate::models::MonsterStats::from_monster(&model);
-                                let msg = WsMessage::OpponentCharacterSelected {
-                                    character,
-                                    monster_stats: Some(monster_stats),
-                                    timestamp: chrono::Utc::now(),
-                                };
-                                let channels = ws_channels.lock().unwrap();
-                                if let Some(player_map) = channels.get(&matching_id_clone) {
-                                    if let Some((opponent_sender, _)) = player_map.get(&opponent_id)
+                            // 自分のキャラクターを設定して準備完了に
+                            let Some(player) = session.player_mut(&player_id_clone) else {
+                                println!("❌ Player ID mismatch");
+                                return;
+                            };
+                            player.character = Some(character.clone());
+                            player.ready = true;
+                            let opponent_ids = session.other_player_ids(&player_id_clone);
+
+                            println!("🎯 opponent_ids: {:?}", opponent_ids);
+
+                            // 他の参加者に通知（モンスターステータスを含む）
+                            let msg = WsMessage::OpponentCharacterSelected {
+                                player_id: player_id_clone.clone(),
+                                character,
+                                monster_stats: Some(monster_stats),
+                                timestamp: chrono::Utc::now(),
+                            };
+                            let channels = ws_channels.lock().unwrap();
+                            if let Some(player_map) = channels.get(&matching_id_clone) {
+                                for opponent_id in &opponent_ids {
+                                    if let Some((opponent_sender, _)) = player_map.get(opponent_id)
                                     {
                                         println!(
                                             "✅ Sending OpponentCharacterSelected with monster stats to opponent: {}",
                                             opponent_id
                                         );
-                                        let _ = opponent_sender.send(msg);
+                                        let _ = opponent_sender.send(msg.clone());
                                     } else {
                                         println!(
                                             "❌ opponent_sender not found for opponent_id: {}",
                                             opponent_id
                                         );
                                     }
-                                } else {
-                                    println!(
-                                        "❌ player_map not found for matching_id: {}",
-                                        matching_id_clone
-                                    );
                                 }
                             } else {
-                                println!("❌ opponent_id is None, cannot send message");
+                                println!(
+                                    "❌ player_map not found for matching_id: {}",
+                                    matching_id_clone
+                                );
                             }
+                            drop(channels);
 
                             println!(
-                                "📊 Ready status: player_a={}, player_b={}",
-                                session.player_a.ready,
-                                session.player_b.as_ref().map_or(false, |p| p.ready)
+                                "📊 Ready status: {:?}",
+                                session
+                                    .players
+                                    .iter()
+                                    .map(|p| (p.id.as_str(), p.ready))
+                                    .collect::<Vec<_>>()
                             );
 
-                            // 両者準備完了でゲーム開始
-                            if session.is_both_ready() {
-                                println!("🎮 Both players ready, starting game...");
+                            // 全員準備完了でゲーム開始
+                            if session.is_all_ready() {
+                                println!("🎮 All players ready, starting game...");
 
                                 // キャラクター選択チェック
-                                let player_a_char = match session.player_a.character.clone() {
-                                    Some(c) => c,
-                                    None => {
-                                        println!("❌ player_a has not selected a character yet");
-                                        let error_msg = WsMessage::Error {
-                                            message: "Player A has not selected a character"
-                                                .to_string(),
-                                        };
-                                        let _ = tx.send(error_msg);
-                                        return;
-                                    }
-                                };
-                                let player_b_char = match session
-                                    .player_b
-                                    .as_ref()
-                                    .and_then(|p| p.character.clone())
-                                {
-                                    Some(c) => c,
-                                    None => {
-                                        println!("❌ player_b has not selected a character yet");
-                                        let error_msg = WsMessage::Error {
-                                            message: "Player B has not selected a character"
-                                                .to_string(),
-                                        };
-                                        let _ = tx.send(error_msg);
-                                        return;
-                                    }
+                                let players: Option<Vec<(String, crate::models::Character)>> =
+                                    session
+                                        .players
+                                        .iter()
+                                        .map(|p| p.character.clone().map(|c| (p.id.clone(), c)))
+                                        .collect();
+                                let Some(players) = players else {
+                                    println!("❌ A player has not selected a character yet");
+                                    let error_msg = WsMessage::Error {
+                                        message: "A player has not selected a character"
+                                            .to_string(),
+                                    };
+                                    let _ = tx.send(error_msg);
+                                    return;
                                 };
 
-                                println!("✅ Both players have selected characters");
+                                println!("✅ All players have selected characters");
                                 session.status = MatchingStatus::InGame;
                                 session.is_battle_started = true;
 
-                                let player_a_id = session.player_a.id.clone();
-                                let player_b_id = session.player_b.as_ref().unwrap().id.clone();
+                                let settings = session.settings.clone();
+                                let teams = session.teams();
                                 drop(sessions_lock); // ロック解除
 
                                 // ゲームマネージャーに開始を通知
@@ -578,16 +780,15 @@ impl WsSession {
                                 // The original code had:
                                 // let game = GameStateManager::new(...);
                                 // self.game_manager.do_send(StartGame { game, ws_senders });
-                                // This `game` variable is created *inside* the `if session.is_both_ready()` block.
+                                // This `game` variable is created *inside* the `if session.is_all_ready()` block.
                                 // So, it should be created here.
 
-                                let game = GameStateManager::new(
-                                    matching_id_clone,
-                                    player_a_id.clone(),
-                                    player_b_id.clone(),
-                                    player_a_char,
-                                    player_b_char,
-                                );
+                                let game = GameStateManager::new(matching_id_clone, players)
+                                    .with_time_limit(settings.time_limit_seconds)
+                                    .with_rounds(settings.rounds)
+                                    .with_snapshot_interval(settings.snapshot_interval_ticks)
+                                    .with_arena(arenas.get(&settings.arena_id).cloned())
+                                    .with_teams(teams, settings.friendly_fire);
 
                                 // This `game_manager` needs to be cloned outside the async block.
                                 // For now, I'll assume it's available or will be added.
@@ -656,7 +857,7 @@ impl WsSession {
     }
 
     /// 入力処理
-    fn handle_input(&mut self, action: crate::models::InputAction) {
+    fn handle_input(&mut self, action: crate::models::InputAction, seq: Option<u32>) {
         let Some(player_id) = &self.player_id else {
             return;
         };
@@ -667,6 +868,7 @@ impl WsSession {
         let input = crate::models::PlayerInput {
             player_id: player_id.clone(),
             action,
+            seq,
             timestamp: chrono::Utc::now(),
         };
 
@@ -681,6 +883,7 @@ impl WsSession {
         &mut self,
         position: crate::models::Vector3,
         rotation: crate::models::Vector3,
+        seq: Option<u32>,
     ) {
         let Some(player_id) = &self.player_id else {
             return;
@@ -695,8 +898,405 @@ impl WsSession {
             player_id: player_id.clone(),
             position,
             rotation,
+            seq,
+        });
+    }
+
+    /// 被弾報告処理
+    fn handle_damage_report(&mut self, attack_type: crate::models::AttackType) {
+        let Some(player_id) = &self.player_id else {
+            return;
+        };
+        let Some(matching_id) = &self.matching_id else {
+            return;
+        };
+
+        // マッチングがゲーム中であり、送信者が参加者であることを確認
+        let is_participant = {
+            let sessions = self.sessions.lock().unwrap();
+            sessions.get(matching_id).is_some_and(|session| {
+                session.status == MatchingStatus::InGame && session.is_participant(player_id)
+            })
+        };
+
+        if !is_participant {
+            println!(
+                "❌ DamageReport rejected: player_id={} is not in game {}",
+                player_id, matching_id
+            );
+            let _ = self.tx.send(WsMessage::Error {
+                message: "You are not in an active game".to_string(),
+            });
+            return;
+        }
+
+        use crate::game::manager::ApplyDamage;
+        self.game_manager.do_send(ApplyDamage {
+            matching_id: *matching_id,
+            player_id: player_id.clone(),
+            attack_type,
         });
     }
+
+    /// スナップショットACK処理
+    fn handle_snapshot_ack(&mut self, tick: u64) {
+        let Some(player_id) = &self.player_id else {
+            return;
+        };
+        let Some(matching_id) = &self.matching_id else {
+            return;
+        };
+
+        use crate::game::manager::AcknowledgeSnapshot;
+        self.game_manager.do_send(AcknowledgeSnapshot {
+            matching_id: *matching_id,
+            player_id: player_id.clone(),
+            tick,
+        });
+    }
+
+    /// 降参処理
+    fn handle_surrender(&mut self) {
+        let Some(player_id) = &self.player_id else {
+            return;
+        };
+        let Some(matching_id) = &self.matching_id else {
+            return;
+        };
+
+        let is_participant = {
+            let sessions = self.sessions.lock().unwrap();
+            sessions.get(matching_id).is_some_and(|session| {
+                session.status == MatchingStatus::InGame && session.is_participant(player_id)
+            })
+        };
+
+        if !is_participant {
+            println!(
+                "❌ Surrender rejected: player_id={} is not in game {}",
+                player_id, matching_id
+            );
+            let _ = self.tx.send(WsMessage::Error {
+                message: "You are not in an active game".to_string(),
+            });
+            return;
+        }
+
+        use crate::game::manager::Surrender;
+        self.game_manager.do_send(Surrender {
+            matching_id: *matching_id,
+            player_id: player_id.clone(),
+        });
+    }
+
+    /// 再戦申し込み処理
+    fn handle_request_rematch(&mut self) {
+        let Some(player_id) = &self.player_id else {
+            return;
+        };
+        let Some(matching_id) = &self.matching_id else {
+            return;
+        };
+
+        let mut sessions = self.sessions.lock().unwrap();
+
+        // ボットは再戦の申し込みに応じないため、ボット対戦の再戦はすぐに拒否する
+        if sessions
+            .get(matching_id)
+            .is_some_and(|session| session.settings.bot_difficulty.is_some())
+        {
+            println!(
+                "❌ RequestRematch rejected in bot match: player_id={}, matching_id={}",
+                player_id, matching_id
+            );
+            let _ = self.tx.send(WsMessage::Error {
+                message: "Rematch is not available in bot matches".to_string(),
+            });
+            return;
+        }
+
+        let Some(session) = sessions
+            .get_mut(matching_id)
+            .filter(|session| session.is_participant(player_id) && session.is_rematch_open())
+        else {
+            println!(
+                "❌ RequestRematch rejected: player_id={}, matching_id={}",
+                player_id, matching_id
+            );
+            let _ = self.tx.send(WsMessage::Error {
+                message: "Rematch is not available".to_string(),
+            });
+            return;
+        };
+
+        session.rematch_requested_by = Some(player_id.clone());
+        let rematch_deadline = session.rematch_deadline.unwrap_or_else(chrono::Utc::now);
+        let opponent_ids = session.other_player_ids(player_id);
+        drop(sessions);
+
+        println!(
+            "🔁 Rematch requested: player_id={}, matching_id={}",
+            player_id, matching_id
+        );
+
+        // 他の参加者に再戦申し込みを通知
+        let channels = self.ws_channels.lock().unwrap();
+        let Some(player_map) = channels.get(matching_id) else {
+            return;
+        };
+        for (sender, _) in opponent_ids.iter().filter_map(|id| player_map.get(id)) {
+            let _ = sender.send(WsMessage::RematchRequested {
+                requester_id: player_id.clone(),
+                rematch_deadline,
+                timestamp: chrono::Utc::now(),
+            });
+        }
+    }
+
+    /// 再戦承諾処理
+    fn handle_accept_rematch(&mut self) {
+        let Some(player_id) = &self.player_id else {
+            return;
+        };
+        let Some(matching_id) = &self.matching_id else {
+            return;
+        };
+
+        // 相手からの申し込みが受付期限内にある場合のみ承諾できる
+        let mut sessions = self.sessions.lock().unwrap();
+        let Some(session) = sessions.get_mut(matching_id).filter(|session| {
+            session.is_participant(player_id)
+                && session.is_rematch_open()
+                && session
+                    .rematch_requested_by
+                    .as_ref()
+                    .is_some_and(|requester_id| requester_id != player_id)
+        }) else {
+            println!(
+                "❌ AcceptRematch rejected: player_id={}, matching_id={}",
+                player_id, matching_id
+            );
+            let _ = self.tx.send(WsMessage::Error {
+                message: "No rematch request to accept".to_string(),
+            });
+            return;
+        };
+
+        session.reset_for_rematch();
+        drop(sessions);
+
+        println!(
+            "🔁 Rematch accepted: player_id={}, matching_id={}",
+            player_id, matching_id
+        );
+
+        // 参加者全員に通知（キャラクター選択からやり直す）
+        let channels = self.ws_channels.lock().unwrap();
+        if let Some(player_map) = channels.get(matching_id) {
+            let msg = WsMessage::RematchAccepted {
+                matching_id: *matching_id,
+                timestamp: chrono::Utc::now(),
+            };
+            for (sender, _) in player_map.values() {
+                let _ = sender.send(msg.clone());
+            }
+        }
+    }
+
+    /// 観戦開始処理
+    /// マッチングに参加していない場合のみ、指定したマッチングの観戦者としてGameManagerに登録する
+    fn handle_spectate(&mut self, matching_id: Uuid, ctx: &mut ws::WebsocketContext<Self>) {
+        if let Some(current) = self.matching_id {
+            println!(
+                "❌ Spectate rejected: player_id={:?} is in matching {}",
+                self.player_id, current
+            );
+            let _ = self.tx.send(WsMessage::Error {
+                message: "Cannot spectate while in a match".to_string(),
+            });
+            return;
+        }
+
+        let game_manager = self.game_manager.clone();
+        let add_spectator = AddSpectator {
+            matching_id,
+            spectator_id: self.session_id,
+            sender: self.tx.clone(),
+        };
+        ctx.spawn(
+            async move { game_manager.send(add_spectator).await }
+                .into_actor(self)
+                .map(move |result, act, _ctx| match result {
+                    Ok(Ok(_)) => {
+                        // 観戦者はロビーのマッチング一覧の通知対象から外す
+                        if let Some(player_id) = &act.player_id {
+                            act.lobby_players.lock().unwrap().remove(player_id);
+                        }
+                        act.spectating = Some(matching_id);
+                    }
+                    Ok(Err(message)) => {
+                        println!(
+                            "❌ Spectate rejected: matching_id={}, {}",
+                            matching_id, message
+                        );
+                        let _ = act.tx.send(WsMessage::Error { message });
+                    }
+                    Err(e) => {
+                        println!("❌ Failed to register spectator: {}", e);
+                        let _ = act.tx.send(WsMessage::Error {
+                            message: "Failed to start spectating".to_string(),
+                        });
+                    }
+                }),
+        );
+    }
+
+    /// リプレイ再生要求処理
+    /// マッチングに参加していない場合のみ、保存済みのリプレイを読み込んで再生する
+    fn handle_watch_replay(
+        &mut self,
+        game_id: Uuid,
+        speed: Option<f32>,
+        ctx: &mut ws::WebsocketContext<Self>,
+    ) {
+        if let Some(current) = self.matching_id {
+            println!(
+                "❌ WatchReplay rejected: player_id={:?} is in matching {}",
+                self.player_id, current
+            );
+            let _ = self.tx.send(WsMessage::Error {
+                message: "Cannot watch a replay while in a match".to_string(),
+            });
+            return;
+        }
+        let speed = match validate_replay_speed(speed.unwrap_or(DEFAULT_REPLAY_SPEED)) {
+            Ok(speed) => speed,
+            Err(message) => {
+                let _ = self.tx.send(WsMessage::Error { message });
+                return;
+            }
+        };
+
+        let replays = self.replays.clone();
+        ctx.spawn(
+            async move { replays.load(&game_id).await }
+                .into_actor(self)
+                .map(move |result, act, ctx| match result {
+                    Ok(Some(replay)) => {
+                        // リプレイ再生中はロビーのマッチング一覧の通知対象から外す
+                        if let Some(player_id) = &act.player_id {
+                            act.lobby_players.lock().unwrap().remove(player_id);
+                        }
+                        act.begin_replay(replay, speed);
+                        act.run_replay(ctx);
+                    }
+                    Ok(None) => {
+                        println!("❌ Replay not found: game_id={}", game_id);
+                        let _ = act.tx.send(WsMessage::Error {
+                            message: "Replay not found".to_string(),
+                        });
+                    }
+                    Err(e) => {
+                        println!("❌ Failed to load replay {}: {}", game_id, e);
+                        let _ = act.tx.send(WsMessage::Error {
+                            message: "Failed to load replay".to_string(),
+                        });
+                    }
+                }),
+        );
+    }
+
+    /// 再生速度の変更処理
+    fn handle_set_replay_speed(&mut self, speed: f32) {
+        let Some(playback) = self.replay.as_mut() else {
+            let _ = self.tx.send(WsMessage::Error {
+                message: "No replay is playing".to_string(),
+            });
+            return;
+        };
+        match validate_replay_speed(speed) {
+            Ok(speed) => {
+                println!(
+                    "🎞️ Replay speed changed: matching_id={}, speed={}",
+                    playback.matching_id, speed
+                );
+                playback.set_speed(speed);
+            }
+            Err(message) => {
+                let _ = self.tx.send(WsMessage::Error { message });
+            }
+        }
+    }
+
+    /// リプレイを先頭から再生する準備（ReplayStartedを送信）
+    fn begin_replay(&mut self, replay: Replay, speed: f32) {
+        println!(
+            "🎞️ Starting replay: matching_id={}, game_id={}, events={}, speed={}",
+            replay.matching_id,
+            replay.game_id,
+            replay.events.len(),
+            speed
+        );
+        let _ = self.tx.send(WsMessage::ReplayStarted {
+            matching_id: replay.matching_id,
+            game_id: replay.game_id,
+            player_ids: replay.player_ids.clone(),
+            teams: replay.teams.clone(),
+            arena_id: replay.arena_id.clone(),
+            rounds: replay.rounds,
+            time_limit_seconds: replay.time_limit_seconds,
+            duration_ms: replay.duration_ms,
+            event_count: replay.events.len(),
+            speed,
+            timestamp: chrono::Utc::now(),
+        });
+        self.replay = Some(ReplayPlayback::new(replay, speed));
+        self.replay_mode = true;
+    }
+
+    /// 再生位置に到達したイベントをReplayFrameとして送信し、最後まで再生したらReplayFinishedを送信
+    fn run_replay(&mut self, ctx: &mut ws::WebsocketContext<Self>) {
+        if let Some(handle) = self.replay_handle.take() {
+            ctx.cancel_future(handle);
+        }
+        let mut last_frame_at = Instant::now();
+        let handle = ctx.run_interval(
+            Duration::from_millis(REPLAY_FRAME_INTERVAL_MS),
+            move |act, ctx| {
+                let now = Instant::now();
+                let elapsed_ms = now.duration_since(last_frame_at).as_secs_f64() * 1000.0;
+                last_frame_at = now;
+                let Some(playback) = act.replay.as_mut() else {
+                    return;
+                };
+
+                let timestamp = chrono::Utc::now();
+                for event in playback.advance(elapsed_ms) {
+                    let _ = act.tx.send(WsMessage::ReplayFrame {
+                        tick: event.tick,
+                        offset_ms: event.offset_ms,
+                        event: event.event,
+                        timestamp,
+                    });
+                }
+                if !playback.is_finished() {
+                    return;
+                }
+
+                println!("🎞️ Replay finished: matching_id={}", playback.matching_id);
+                let _ = act.tx.send(WsMessage::ReplayFinished {
+                    matching_id: playback.matching_id,
+                    result: playback.result().cloned(),
+                    timestamp,
+                });
+                act.replay = None;
+                if let Some(handle) = act.replay_handle.take() {
+                    ctx.cancel_future(handle);
+                }
+            },
+        );
+        self.replay_handle = Some(handle);
+    }
 }
 
 impl Actor for WsSession {
@@ -705,9 +1305,22 @@ impl Actor for WsSession {
     fn started(&mut self, ctx: &mut Self::Context) {
         self.hb(ctx);
         self.poll_messages(ctx);
+
+        // ?replay=で接続した場合はすぐに再生を開始
+        if self.replay.is_some() {
+            self.run_replay(ctx);
+        }
     }
 
     fn stopped(&mut self, _ctx: &mut Self::Context) {
+        // 観戦者の登録を解除
+        if let Some(matching_id) = self.spectating {
+            self.game_manager.do_send(RemoveSpectator {
+                matching_id,
+                spectator_id: self.session_id,
+            });
+        }
+
         // マッチング待ちリストから自分を削除
         // マッチング待ちリストから自分を削除
         if let Some(player_id) = &self.player_id {
@@ -745,8 +1358,15 @@ impl Actor for WsSession {
 
         // WsChannelsから自分を削除
         if let (Some(matching_id), Some(player_id)) = (self.matching_id, &self.player_id) {
+            let is_bot_match = self
+                .sessions
+                .lock()
+                .unwrap()
+                .get(&matching_id)
+                .is_some_and(|session| session.settings.bot_difficulty.is_some());
             let mut channels = self.ws_channels.lock().unwrap();
             let mut is_empty = false;
+            let mut removed = false;
             if let Some(player_map) = channels.get_mut(&matching_id) {
                 let should_remove = if let Some((_, sid)) = player_map.get(player_id) {
                     *sid == self.session_id
@@ -756,6 +1376,12 @@ impl Actor for WsSession {
 
                 if should_remove {
                     player_map.remove(player_id);
+                    removed = true;
+                    // ボット対戦で人間がいなくなったらボットのチャンネルも削除する
+                    // （試合開始前のボットは受信が終わると停止し、試合中のボットは試合終了で停止する）
+                    if is_bot_match {
+                        player_map.clear();
+                    }
                 }
                 // マッチングIDに対応するエントリが空になったら、そのエントリ自体を削除
                 if player_map.is_empty() {
@@ -765,6 +1391,34 @@ impl Actor for WsSession {
             }
             drop(channels);
 
+            if removed {
+                let mut sessions = self.sessions.lock().unwrap();
+                let in_game = sessions
+                    .get(&matching_id)
+                    .is_some_and(|session| session.status == MatchingStatus::InGame);
+                // 成立前に切断した参加者は外す（準備完了しない参加者が残って開始できなくなるため）
+                let left_before_matched = sessions
+                    .get_mut(&matching_id)
+                    .is_some_and(|session| session.remove_waiting_player(player_id));
+                drop(sessions);
+
+                // ゲーム中の切断はゲームマネージャーに通知（一時停止して再接続を待つ）
+                if in_game {
+                    use crate::game::manager::PlayerDisconnected;
+                    self.game_manager.do_send(PlayerDisconnected {
+                        matching_id,
+                        player_id: player_id.clone(),
+                    });
+                }
+                if left_before_matched {
+                    println!(
+                        "👋 Player left before the match was established: matching_id={}, player_id={}",
+                        matching_id, player_id
+                    );
+                    self.broadcast_update_matchings();
+                }
+            }
+
             // 誰もいなくなったら last_active_at を設定
             if is_empty {
                 let mut sessions = self.sessions.lock().unwrap();
@@ -793,29 +1447,135 @@ impl StreamHandler<Result<ws::Message, ws::ProtocolError>> for WsSession {
             Ok(ws::Message::Text(text)) => {
                 println!("📨 Received WebSocket message: {}", text);
                 if let Ok(ws_msg) = serde_json::from_str::<WsMessage>(&text) {
+                    // 観戦者は読み取り専用
+                    if let Some(matching_id) = self.spectating {
+                        println!(
+                            "🚫 Rejected message from spectator: matching_id={}",
+                            matching_id
+                        );
+                        let _ = self.tx.send(WsMessage::Error {
+                            message: "Spectators cannot send messages".to_string(),
+                        });
+                        return;
+                    }
+                    // リプレイ再生モードでは再生の操作のみ受け付ける
+                    if self.replay_mode
+                        && !matches!(
+                            ws_msg,
+                            WsMessage::WatchReplay { .. } | WsMessage::SetReplaySpeed { .. }
+                        )
+                    {
+                        println!("🚫 Rejected message from replay viewer");
+                        let _ = self.tx.send(WsMessage::Error {
+                            message: "Replay viewers can only control playback".to_string(),
+                        });
+                        return;
+                    }
                     match ws_msg {
-                        WsMessage::CreateMatching { username } => {
-                            println!("✅ Handling CreateMatching with username={:?}", username);
-                            self.handle_create_matching(username, ctx);
+                        WsMessage::CreateMatching {
+                            username,
+                            time_limit_seconds,
+                            rounds,
+                            snapshot_interval_ticks,
+                            arena_id,
+                            vs_bot,
+                            bot_difficulty,
+                            max_players,
+                            team_mode,
+                            friendly_fire,
+                        } => {
+                            println!(
+                                "✅ Handling CreateMatching with username={:?}, time_limit_seconds={:?}, rounds={:?}, snapshot_interval_ticks={:?}, arena_id={:?}, vs_bot={:?}, bot_difficulty={:?}, max_players={:?}, team_mode={:?}, friendly_fire={:?}",
+                                username,
+                                time_limit_seconds,
+                                rounds,
+                                snapshot_interval_ticks,
+                                arena_id,
+                                vs_bot,
+                                bot_difficulty,
+                                max_players,
+                                team_mode,
+                                friendly_fire
+                            );
+                            let defaults = MatchSettings::default();
+                            let settings = MatchSettings {
+                                time_limit_seconds: time_limit_seconds
+                                    .unwrap_or(defaults.time_limit_seconds),
+                                rounds: rounds.unwrap_or(defaults.rounds),
+                                snapshot_interval_ticks,
+                                arena_id: arena_id.unwrap_or(defaults.arena_id),
+                                bot_difficulty: vs_bot
+                                    .unwrap_or(false)
+                                    .then(|| bot_difficulty.unwrap_or_default()),
+                                max_players: max_players.unwrap_or(if team_mode == Some(true) {
+                                    DEFAULT_TEAM_MAX_PLAYERS
+                                } else {
+                                    defaults.max_players
+                                }),
+                                team_mode: team_mode.unwrap_or(defaults.team_mode),
+                                friendly_fire: friendly_fire.unwrap_or(defaults.friendly_fire),
+                            };
+                            self.handle_create_matching(username, settings, ctx);
                         }
-                        WsMessage::JoinMatch { matching_id } => {
-                            println!("✅ Handling JoinMatch: matching_id={}", matching_id);
-                            self.handle_join_match(matching_id, ctx);
+                        WsMessage::JoinMatch { matching_id, team } => {
+                            println!(
+                                "✅ Handling JoinMatch: matching_id={}, team={:?}",
+                                matching_id, team
+                            );
+                            self.handle_join_match(matching_id, team, ctx);
                         }
                         WsMessage::Ready { selected_model_id } => {
                             println!("✅ Handling Ready: selected_model_id={}", selected_model_id);
                             self.handle_ready(selected_model_id, ctx);
                         }
-                        WsMessage::Input { action } => {
-                            println!("🎯 Handling Input: action={:?}", action);
-                            self.handle_input(action);
+                        WsMessage::Input { action, seq } => {
+                            println!("🎯 Handling Input: action={:?}, seq={:?}", action, seq);
+                            self.handle_input(action, seq);
                         }
-                        WsMessage::StateUpdate { position, rotation } => {
+                        WsMessage::StateUpdate {
+                            position,
+                            rotation,
+                            seq,
+                        } => {
                             println!(
-                                "🔄 Handling StateUpdate: position={:?}, rotation={:?}",
-                                position, rotation
+                                "🔄 Handling StateUpdate: position={:?}, rotation={:?}, seq={:?}",
+                                position, rotation, seq
                             );
-                            self.handle_state_update(position, rotation);
+                            self.handle_state_update(position, rotation, seq);
+                        }
+                        WsMessage::DamageReport { attack_type } => {
+                            println!("💥 Handling DamageReport: attack_type={}", attack_type);
+                            self.handle_damage_report(attack_type);
+                        }
+                        WsMessage::Surrender => {
+                            println!("🏳️ Handling Surrender");
+                            self.handle_surrender();
+                        }
+                        WsMessage::SnapshotAck { tick } => {
+                            self.handle_snapshot_ack(tick);
+                        }
+                        WsMessage::RequestRematch => {
+                            println!("🔁 Handling RequestRematch");
+                            self.handle_request_rematch();
+                        }
+                        WsMessage::AcceptRematch => {
+                            println!("🔁 Handling AcceptRematch");
+                            self.handle_accept_rematch();
+                        }
+                        WsMessage::Spectate { matching_id } => {
+                            println!("👀 Handling Spectate: matching_id={}", matching_id);
+                            self.handle_spectate(matching_id, ctx);
+                        }
+                        WsMessage::WatchReplay { game_id, speed } => {
+                            println!(
+                                "🎞️ Handling WatchReplay: game_id={}, speed={:?}",
+                                game_id, speed
+                            );
+                            self.handle_watch_replay(game_id, speed, ctx);
+                        }
+                        WsMessage::SetReplaySpeed { speed } => {
+                            println!("🎞️ Handling SetReplaySpeed: speed={}", speed);
+                            self.handle_set_replay_speed(speed);
                         }
                         _ => {
                             println!("⚠️ Unhandled message type");
@@ -855,24 +1615,78 @@ impl StreamHandler<Result<ws::Message, ws::ProtocolError>> for WsSession {
 pub async fn ws_handler(
     req: HttpRequest,
     stream: web::Payload,
-    db_pool: web::Data<SqlitePool>,
-    sessions: web::Data<MatchingSessions>,
-    ws_channels: web::Data<WsChannels>,
-    waiting_players: web::Data<WaitingPlayers>,
-    lobby_players: web::Data<LobbyPlayers>,
-    game_manager: web::Data<Addr<GameManager>>,
+    state: web::Data<AppState>,
     query: web::Query<std::collections::HashMap<String, String>>,
 ) -> Result<HttpResponse, Error> {
     println!("🔌 WebSocket connection attempt: query={:?}", query);
 
-    let mut ws_session = WsSession::new(
-        sessions.get_ref().clone(),
-        ws_channels.get_ref().clone(),
-        waiting_players.get_ref().clone(),
-        lobby_players.get_ref().clone(),
-        game_manager.get_ref().clone(),
-        db_pool.get_ref().clone(),
-    );
+    let mut ws_session = WsSession::new(state.get_ref().clone());
+
+    // 観戦モード（?spectate=<matching_id>）はプレイヤーとして登録せず、観戦者として登録する
+    if let Some(spectate) = query.get("spectate") {
+        let Ok(matching_id) = Uuid::parse_str(spectate) else {
+            return Err(actix_web::error::ErrorBadRequest(
+                "Invalid matching_id to spectate",
+            ));
+        };
+        let spectator_id = ws_session.session_id;
+        let registered = state
+            .game_manager
+            .send(AddSpectator {
+                matching_id,
+                spectator_id,
+                sender: ws_session.tx.clone(),
+            })
+            .await
+            .map_err(actix_web::error::ErrorInternalServerError)?;
+        if let Err(message) = registered {
+            println!(
+                "❌ Spectate rejected: matching_id={}, {}",
+                matching_id, message
+            );
+            return Err(actix_web::error::ErrorBadRequest(message));
+        }
+        println!(
+            "👀 WebSocket connected as spectator: matching_id={}",
+            matching_id
+        );
+        ws_session.spectating = Some(matching_id);
+
+        let response = ws::start(ws_session, &req, stream);
+        if response.is_err() {
+            state.game_manager.do_send(RemoveSpectator {
+                matching_id,
+                spectator_id,
+            });
+        }
+        return response;
+    }
+
+    // リプレイ再生モード（?replay=<game_id>&speed=1.0）は記録済みのイベントを再生する
+    if let Some(replay_id) = query.get("replay") {
+        let Ok(game_id) = Uuid::parse_str(replay_id) else {
+            return Err(actix_web::error::ErrorBadRequest(
+                "Invalid game_id to replay",
+            ));
+        };
+        let speed = match query.get("speed") {
+            Some(speed) => speed
+                .parse::<f32>()
+                .map_err(|_| "speed must be a number".to_string())
+                .and_then(validate_replay_speed),
+            None => Ok(DEFAULT_REPLAY_SPEED),
+        }
+        .map_err(actix_web::error::ErrorBadRequest)?;
+        let replay = state
+            .replays
+            .load(&game_id)
+            .await
+            .map_err(actix_web::error::ErrorInternalServerError)?
+            .ok_or_else(|| actix_web::error::ErrorNotFound("Replay not found"))?;
+        println!("🎞️ WebSocket connected for replay: game_id={}", game_id);
+        ws_session.begin_replay(replay, speed);
+        return ws::start(ws_session, &req, stream);
+    }
 
     // クエリパラメータからplayer_idを取得（なければ生成）
     let player_id = if let Some(player_id) = query.get("player_id") {
@@ -891,7 +1705,7 @@ pub async fn ws_handler(
 
             // セッションの有効性チェックと last_active_at のクリア
             {
-                let mut sessions = sessions.lock().unwrap();
+                let mut sessions = state.sessions.lock().unwrap();
                 if let Some(session) = sessions.get_mut(&id) {
                     if !session.is_valid() {
                         println!("❌ Matching session {} is expired", id);
@@ -912,7 +1726,7 @@ pub async fn ws_handler(
 
             // WsChannelsに登録
             if let Some(player_id) = &ws_session.player_id {
-                let mut channels = ws_channels.lock().unwrap();
+                let mut channels = state.ws_channels.lock().unwrap();
                 let player_map = channels.entry(id).or_default();
                 player_map.insert(
                     player_id.clone(),
@@ -932,12 +1746,10 @@ pub async fn ws_handler(
             // マッチング成功を通知
             let sessions = ws_session.sessions.lock().unwrap();
             if let Some(session) = sessions.get(&id) {
-                let opponent_id = if session.player_a.id == *ws_session.player_id.as_ref().unwrap()
-                {
-                    session.player_b.as_ref().map(|p| p.id.clone())
-                } else {
-                    Some(session.player_a.id.clone())
-                };
+                let opponent_id = session
+                    .other_player_ids(ws_session.player_id.as_ref().unwrap())
+                    .into_iter()
+                    .next();
 
                 if let Some(opponent_id) = opponent_id {
                     let msg = WsMessage::MatchingSuccess {
@@ -947,6 +1759,16 @@ pub async fn ws_handler(
                     };
                     let _ = ws_session.tx.send(msg);
                 }
+
+                // ゲーム中の再接続は送信チャンネルを差し替えて状態を再同期
+                if session.status == MatchingStatus::InGame {
+                    use crate::game::manager::RebindPlayer;
+                    state.game_manager.do_send(RebindPlayer {
+                        matching_id: id,
+                        player_id: ws_session.player_id.clone().unwrap(),
+                        sender: ws_session.tx.clone(),
+                    });
+                }
             }
         }
     }
@@ -954,7 +1776,7 @@ pub async fn ws_handler(
     // ロビー待機リストに追加（マッチングに参加していない場合）
     if ws_session.matching_id.is_none() {
         if let Some(player_id) = &ws_session.player_id {
-            let mut lobby_players = lobby_players.lock().unwrap();
+            let mut lobby_players = state.lobby_players.lock().unwrap();
             lobby_players.insert(
                 player_id.clone(),
                 (ws_session.tx.clone(), ws_session.session_id),
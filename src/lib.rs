@@ -1,5 +1,6 @@
 pub mod db;
 pub mod models;
 pub mod utils;
+pub mod geometry;
 pub mod game;
 pub mod handlers;
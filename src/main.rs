@@ -1,5 +1,6 @@
 mod db;
 mod game;
+mod geometry;
 mod handlers;
 mod models;
 mod utils;
@@ -8,8 +9,13 @@ use actix::Actor;
 use actix_files as fs;
 use actix_web::{App, HttpServer, web};
 use db::init_db;
+use game::arena::{ARENA_DATA_DIR, ArenaRegistry};
 use game::manager::GameManager;
-use handlers::{MatchingSessions, WaitingPlayers, WsChannels, upload_model, ws_handler};
+use game::replay::{REPLAY_DATA_DIR, ReplayStore};
+use handlers::{
+    AppState, Arenas, MatchingSessions, Replays, WaitingPlayers, WsChannels, get_replay,
+    upload_model, ws_handler,
+};
 use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
 
@@ -35,6 +41,15 @@ async fn main() -> std::io::Result<()> {
     // テストモデルを自動登録
     db::load_test_models(&db_pool).await;
 
+    // アリーナ定義を読み込み
+    let arenas: Arenas = Arc::new(
+        ArenaRegistry::load_dir(ARENA_DATA_DIR).expect("Failed to load arena definitions"),
+    );
+    println!("✅ Arenas loaded: {:?}", arenas.ids());
+
+    // リプレイの保存先
+    let replays: Replays = Arc::new(ReplayStore::new(REPLAY_DATA_DIR));
+
     // 共有状態初期化
     let matching_sessions: MatchingSessions = Arc::new(Mutex::new(HashMap::new()));
     let ws_channels: WsChannels = Arc::new(Mutex::new(HashMap::new()));
@@ -42,7 +57,22 @@ async fn main() -> std::io::Result<()> {
     let lobby_players: handlers::LobbyPlayers = Arc::new(Mutex::new(HashMap::new()));
 
     // ゲームマネージャーアクター起動
-    let game_manager = GameManager::new(matching_sessions.clone()).start();
+    let game_manager = GameManager::new(matching_sessions.clone())
+        .with_replays(replays.clone())
+        .with_db(db_pool.clone())
+        .start();
+
+    // WebSocketセッションが共有する状態
+    let app_state = AppState {
+        sessions: matching_sessions,
+        ws_channels,
+        waiting_players,
+        lobby_players,
+        game_manager,
+        db_pool: db_pool.clone(),
+        arenas,
+        replays: replays.clone(),
+    };
 
     println!("✅ Server initialized");
     println!("🌐 Listening on http://0.0.0.0:8080");
@@ -51,13 +81,11 @@ async fn main() -> std::io::Result<()> {
     HttpServer::new(move || {
         App::new()
             .app_data(web::Data::new(db_pool.clone()))
-            .app_data(web::Data::new(matching_sessions.clone()))
-            .app_data(web::Data::new(ws_channels.clone()))
-            .app_data(web::Data::new(waiting_players.clone()))
-            .app_data(web::Data::new(lobby_players.clone()))
-            .app_data(web::Data::new(game_manager.clone()))
+            .app_data(web::Data::new(replays.clone()))
+            .app_data(web::Data::new(app_state.clone()))
             .route("/api/models/upload", web::post().to(upload_model))
             .route("/api/models", web::get().to(handlers::list_models))
+            .route("/api/replays/{game_id}", web::get().to(get_replay))
             .route("/ws", web::get().to(ws_handler))
             // 静的ファイル配信（モデルファイルのダウンロード用）
             .service(fs::Files::new("/uploads", "./uploads").show_files_listing())
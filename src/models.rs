@@ -1,5 +1,6 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use uuid::Uuid;
 
 // モンスターサイズ種別
@@ -11,7 +12,6 @@ pub enum SizeType {
 }
 
 impl SizeType {
-    #[allow(dead_code)]
     pub fn from_str(s: &str) -> Option<Self> {
         match s {
             "Small" => Some(SizeType::Small),
@@ -29,6 +29,15 @@ impl SizeType {
             SizeType::Large => "Large".to_string(),
         }
     }
+
+    /// 当たり判定用のヒットボックス半径
+    pub fn hitbox_radius(&self) -> f32 {
+        match self {
+            SizeType::Small => 0.5,
+            SizeType::Medium => 1.0,
+            SizeType::Large => 1.5,
+        }
+    }
 }
 
 // モンスターステータス情報（クライアント送信用）
@@ -63,7 +72,7 @@ impl MonsterStats {
 }
 
 // 3Dベクトル（位置・方向）
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct Vector3 {
     pub x: f32,
     pub y: f32,
@@ -83,26 +92,35 @@ impl Vector3 {
             z: 0.0,
         }
     }
+
+    /// 全成分が有限の値か（NaN・無限大を含まないか）
+    pub fn is_finite(&self) -> bool {
+        self.x.is_finite() && self.y.is_finite() && self.z.is_finite()
+    }
 }
 
 // 3Dモデルキャラクター情報
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Character {
-    pub model_id: String,  // 3Dモデル識別子
-    pub position: Vector3, // 位置
-    pub rotation: Vector3, // 向き（オイラー角）
-    pub hp: i32,           // HP
-    pub max_hp: i32,       // 最大HP
+    pub model_id: String,    // 3Dモデル識別子
+    pub position: Vector3,   // 位置
+    pub rotation: Vector3,   // 向き（オイラー角）
+    pub hp: i32,             // HP
+    pub max_hp: i32,         // 最大HP
+    pub stats: MonsterStats, // 戦闘ステータス
 }
 
 impl Character {
-    pub fn new(model_id: String) -> Self {
+    /// モンスターのステータスからキャラクターを生成（初期HPは最大HP）
+    pub fn new(model_id: String, stats: MonsterStats) -> Self {
+        let max_hp = stats.max_hp as i32;
         Self {
             model_id,
             position: Vector3::zero(),
             rotation: Vector3::zero(),
-            hp: 100,
-            max_hp: 100,
+            hp: max_hp,
+            max_hp,
+            stats,
         }
     }
 
@@ -119,6 +137,8 @@ pub struct Player {
     pub selected_model_id: Option<String>, // マッチング作成/参加時に選択したモデルID
     pub character: Option<Character>,      // 選択したキャラクター
     pub ready: bool,                       // 準備完了フラグ
+    pub previous_model_id: Option<String>, // 前回のバトルで使用したモデルID（再戦時の再選択用）
+    pub team: Option<Team>,                // 所属チーム（チーム戦以外はNone）
 }
 
 impl Player {
@@ -129,6 +149,8 @@ impl Player {
             selected_model_id: None,
             character: None,
             ready: false,
+            previous_model_id: None,
+            team: None,
         }
     }
 
@@ -139,10 +161,23 @@ impl Player {
             selected_model_id: None,
             character: None,
             ready: false,
+            previous_model_id: None,
+            team: None,
         }
     }
 }
 
+// チーム戦のチーム
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub enum Team {
+    A,
+    B,
+}
+
+impl Team {
+    pub const ALL: [Team; 2] = [Team::A, Team::B];
+}
+
 // マッチングセッション状態
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum MatchingStatus {
@@ -153,59 +188,295 @@ pub enum MatchingStatus {
     Finished,  // 終了
 }
 
+/// バトル終了後に再戦を受け付ける時間（秒）
+pub const REMATCH_WINDOW_SECONDS: i64 = 30;
+
+/// デフォルトの試合時間（秒）
+pub const DEFAULT_TIME_LIMIT_SECONDS: i64 = 180;
+/// 設定可能な試合時間の範囲（秒）
+const MIN_TIME_LIMIT_SECONDS: i64 = 10;
+const MAX_TIME_LIMIT_SECONDS: i64 = 600;
+/// デフォルトのラウンド数
+pub const DEFAULT_ROUNDS: u32 = 1;
+/// 設定可能な最大ラウンド数
+const MAX_ROUNDS: u32 = 9;
+/// スナップショット送信間隔の上限（ティック数、60ティック = 約1秒）
+const MAX_SNAPSHOT_INTERVAL_TICKS: u32 = 60;
+/// アリーナ未指定時のアリーナID
+pub const DEFAULT_ARENA_ID: &str = "colosseum";
+/// デフォルトの参加人数
+pub const DEFAULT_MAX_PLAYERS: usize = 2;
+/// 設定可能な参加人数の範囲
+const MIN_PLAYERS: usize = 2;
+pub const MAX_PLAYERS: usize = 8;
+/// チーム戦のデフォルトの参加人数（2対2）
+pub const DEFAULT_TEAM_MAX_PLAYERS: usize = 4;
+/// 1試合あたりの観戦者数の上限
+pub const MAX_SPECTATORS_PER_MATCH: usize = 8;
+
+// ボット対戦の難易度
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum BotDifficulty {
+    Easy,
+    #[default]
+    Normal,
+    Hard,
+}
+
+// マッチング作成時の試合設定
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MatchSettings {
+    pub time_limit_seconds: i64,               // 1ラウンドの試合時間（秒）
+    pub rounds: u32,                           // ラウンド数（先に過半数を取った方が勝利）
+    pub snapshot_interval_ticks: Option<u32>, // スナップショット送信間隔（ティック数、Noneは送信しない）
+    pub arena_id: String,                     // 対戦するアリーナのID
+    pub bot_difficulty: Option<BotDifficulty>, // ボット対戦の難易度（Noneは対人戦）
+    pub max_players: usize,                   // 参加人数（全員揃うとマッチング成立）
+    pub team_mode: bool,                      // 2チームに分かれて対戦するか
+    pub friendly_fire: bool,                  // チーム戦で味方への攻撃を有効にするか
+}
+
+impl Default for MatchSettings {
+    fn default() -> Self {
+        Self {
+            time_limit_seconds: DEFAULT_TIME_LIMIT_SECONDS,
+            rounds: DEFAULT_ROUNDS,
+            snapshot_interval_ticks: None,
+            arena_id: DEFAULT_ARENA_ID.to_string(),
+            bot_difficulty: None,
+            max_players: DEFAULT_MAX_PLAYERS,
+            team_mode: false,
+            friendly_fire: false,
+        }
+    }
+}
+
+impl MatchSettings {
+    /// 設定値の妥当性を検証
+    pub fn validate(&self) -> Result<(), String> {
+        if !(MIN_TIME_LIMIT_SECONDS..=MAX_TIME_LIMIT_SECONDS).contains(&self.time_limit_seconds) {
+            return Err(format!(
+                "time_limit_seconds must be between {} and {}",
+                MIN_TIME_LIMIT_SECONDS, MAX_TIME_LIMIT_SECONDS
+            ));
+        }
+        // 引き分けなしで過半数が決まるよう奇数のみ許可
+        if self.rounds == 0 || self.rounds > MAX_ROUNDS || self.rounds.is_multiple_of(2) {
+            return Err(format!(
+                "rounds must be an odd number between 1 and {}",
+                MAX_ROUNDS
+            ));
+        }
+        if self
+            .snapshot_interval_ticks
+            .is_some_and(|ticks| ticks == 0 || ticks > MAX_SNAPSHOT_INTERVAL_TICKS)
+        {
+            return Err(format!(
+                "snapshot_interval_ticks must be between 1 and {}",
+                MAX_SNAPSHOT_INTERVAL_TICKS
+            ));
+        }
+        if !(MIN_PLAYERS..=MAX_PLAYERS).contains(&self.max_players) {
+            return Err(format!(
+                "max_players must be between {} and {}",
+                MIN_PLAYERS, MAX_PLAYERS
+            ));
+        }
+        if self.bot_difficulty.is_some() && self.max_players != 2 {
+            return Err("vs_bot matches are limited to 2 players".to_string());
+        }
+        // チーム戦は同じ人数の2チームに分ける
+        if self.team_mode && (self.max_players < 4 || !self.max_players.is_multiple_of(2)) {
+            return Err("team matches require an even max_players of at least 4".to_string());
+        }
+        Ok(())
+    }
+}
+
 // マッチングセッション
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MatchingSession {
     pub matching_id: Uuid,
     pub creator_username: Option<String>, // 作成者のユーザー名
-    pub player_a: Player,
-    pub player_b: Option<Player>,
+    pub players: Vec<Player>,             // 参加者（参加順、先頭が作成者）
     pub status: MatchingStatus,
     pub created_at: DateTime<Utc>,
     pub last_active_at: Option<DateTime<Utc>>, // 最後のプレイヤーが切断した時刻
     pub is_battle_started: bool,               // バトル開始済みフラグ
     pub is_battle_finished: bool,              // バトル終了済みフラグ
+    pub settings: MatchSettings,               // 試合設定
+    pub rematch_deadline: Option<DateTime<Utc>>, // 再戦の受付期限（バトル終了時に設定）
+    pub rematch_requested_by: Option<String>,  // 再戦を申し込んだプレイヤーID
 }
 
 impl MatchingSession {
     #[allow(dead_code)]
-    pub fn new(player_a_id: String) -> Self {
+    pub fn new(creator_id: String) -> Self {
         Self {
             matching_id: Uuid::new_v4(),
             creator_username: None,
-            player_a: Player::new(player_a_id),
-            player_b: None,
+            players: vec![Player::new(creator_id)],
             status: MatchingStatus::Waiting,
             created_at: Utc::now(),
             last_active_at: None,
             is_battle_started: false,
             is_battle_finished: false,
+            settings: MatchSettings::default(),
+            rematch_deadline: None,
+            rematch_requested_by: None,
         }
     }
 
-    pub fn new_with_username(player_a_id: String, username: Option<String>) -> Self {
+    pub fn new_with_username(creator_id: String, username: Option<String>) -> Self {
         Self {
             matching_id: Uuid::new_v4(),
             creator_username: username.clone(),
-            player_a: Player::new_with_username(player_a_id, username),
-            player_b: None,
+            players: vec![Player::new_with_username(creator_id, username)],
             status: MatchingStatus::Waiting,
             created_at: Utc::now(),
             last_active_at: None,
             is_battle_started: false,
             is_battle_finished: false,
+            settings: MatchSettings::default(),
+            rematch_deadline: None,
+            rematch_requested_by: None,
         }
     }
 
-    pub fn is_both_ready(&self) -> bool {
-        self.player_a.ready && self.player_b.as_ref().map_or(false, |p| p.ready)
+    /// 作成者のプレイヤーID
+    pub fn creator_id(&self) -> &str {
+        self.players.first().map_or("", |p| p.id.as_str())
+    }
+
+    /// 参加人数に達したか
+    pub fn is_full(&self) -> bool {
+        self.players.len() >= self.settings.max_players
+    }
+
+    /// 参加者が揃い、全員が準備完了か
+    pub fn is_all_ready(&self) -> bool {
+        self.is_full() && self.players.iter().all(|p| p.ready)
+    }
+
+    /// プレイヤーがこのマッチングの参加者か
+    pub fn is_participant(&self, player_id: &str) -> bool {
+        self.player(player_id).is_some()
+    }
+
+    /// プレイヤーIDから参加者を取得
+    pub fn player(&self, player_id: &str) -> Option<&Player> {
+        self.players.iter().find(|p| p.id == player_id)
+    }
+
+    /// プレイヤーIDから参加者を可変で取得
+    pub fn player_mut(&mut self, player_id: &str) -> Option<&mut Player> {
+        self.players.iter_mut().find(|p| p.id == player_id)
+    }
+
+    /// マッチング成立前に参加者が抜けた場合に参加者から外す（チームの割り当ても解除される）
+    /// 作成者、または待機中でないマッチングの場合は何もせずfalseを返す
+    pub fn remove_waiting_player(&mut self, player_id: &str) -> bool {
+        if self.status != MatchingStatus::Waiting || self.creator_id() == player_id {
+            return false;
+        }
+        let player_count = self.players.len();
+        self.players.retain(|p| p.id != player_id);
+        self.players.len() < player_count
+    }
+
+    /// チームの所属プレイヤーID（参加順）
+    pub fn team_members(&self, team: Team) -> Vec<String> {
+        self.players
+            .iter()
+            .filter(|p| p.team == Some(team))
+            .map(|p| p.id.clone())
+            .collect()
+    }
+
+    /// 参加するプレイヤーのチームを決める（チーム戦以外はNone）
+    /// 希望がなければ人数の少ないチーム（同数ならA）、希望したチームが満員ならエラー
+    pub fn assign_team(&self, requested: Option<Team>) -> Result<Option<Team>, String> {
+        if !self.settings.team_mode {
+            return Ok(None);
+        }
+        let capacity = self.settings.max_players / 2;
+        let team = match requested {
+            Some(team) => team,
+            None => Team::ALL
+                .into_iter()
+                .min_by_key(|team| self.team_members(*team).len())
+                .unwrap_or(Team::A),
+        };
+        if self.team_members(team).len() >= capacity {
+            return Err(format!("Team {:?} is full", team));
+        }
+        Ok(Some(team))
+    }
+
+    /// チームごとの所属プレイヤーと空き枠（チーム戦以外はNone）
+    pub fn team_slots(&self) -> Option<Vec<TeamSlots>> {
+        if !self.settings.team_mode {
+            return None;
+        }
+        let capacity = self.settings.max_players / 2;
+        let slots = Team::ALL
+            .into_iter()
+            .map(|team| {
+                let player_ids = self.team_members(team);
+                TeamSlots {
+                    team,
+                    open_slots: capacity.saturating_sub(player_ids.len()),
+                    player_ids,
+                }
+            })
+            .collect();
+        Some(slots)
+    }
+
+    /// プレイヤーID -> チーム（チーム戦以外は空）
+    pub fn teams(&self) -> HashMap<String, Team> {
+        self.players
+            .iter()
+            .filter_map(|p| p.team.map(|team| (p.id.clone(), team)))
+            .collect()
+    }
+
+    /// 自分以外の参加者のID（参加順）
+    pub fn other_player_ids(&self, player_id: &str) -> Vec<String> {
+        self.players
+            .iter()
+            .filter(|p| p.id != player_id)
+            .map(|p| p.id.clone())
+            .collect()
+    }
+
+    /// 再戦を受け付けているか（バトル終了後、受付期限内）
+    pub fn is_rematch_open(&self) -> bool {
+        self.is_battle_finished
+            && self
+                .rematch_deadline
+                .is_some_and(|deadline| Utc::now() < deadline)
+    }
+
+    /// 再戦のためにセッションを初期化（同じマッチングIDでキャラクター選択からやり直す）
+    pub fn reset_for_rematch(&mut self) {
+        for player in &mut self.players {
+            player.previous_model_id = player.character.take().map(|c| c.model_id);
+            player.ready = false;
+        }
+        self.status = MatchingStatus::Matched;
+        self.is_battle_started = false;
+        self.is_battle_finished = false;
+        self.rematch_deadline = None;
+        self.rematch_requested_by = None;
     }
 
     /// マッチングが有効かどうか判定
-    /// - バトル終了後は無効
+    /// - バトル終了後は再戦の受付期限まで有効
     /// - 両方切断してから60秒経過したら無効
     pub fn is_valid(&self) -> bool {
-        if self.is_battle_finished {
+        if self.is_battle_finished && !self.is_rematch_open() {
             return false;
         }
 
@@ -232,6 +503,7 @@ pub enum InputAction {
         attack_type: AttackType,
         position: Vector3,
         direction: Vector3,
+        view_time: Option<DateTime<Utc>>, // 攻撃時に表示していた相手の状態のサーバー時刻（ラグ補償用）
     },
     Rotate {
         rotation: Vector3,
@@ -239,7 +511,7 @@ pub enum InputAction {
 }
 
 // プレイヤー操作入力
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, sqlx::Type)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash, sqlx::Type)]
 #[sqlx(type_name = "VARCHAR")]
 #[serde(rename_all = "camelCase")]
 pub enum AttackType {
@@ -260,6 +532,7 @@ impl std::fmt::Display for AttackType {
 pub struct PlayerInput {
     pub player_id: String,
     pub action: InputAction,
+    pub seq: Option<u32>, // クライアントのシーケンス番号（クライアント予測の照合用）
     pub timestamp: DateTime<Utc>,
 }
 
@@ -267,23 +540,110 @@ pub struct PlayerInput {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GameState {
     pub matching_id: Uuid,
-    pub player_a: Character,
-    pub player_b: Character,
+    pub characters: HashMap<String, Character>, // player_id -> キャラクター
     pub timestamp: DateTime<Utc>,
 }
 
+// 勝敗種別
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum GameOutcome {
+    Win,  // 勝敗あり
+    Draw, // 引き分け
+}
+
+// ゲーム終了理由
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum EndReason {
+    KnockOut,   // HPが0になった
+    TimeUp,     // 試合時間切れ
+    Surrender,  // 降参
+    Disconnect, // 切断から復帰しなかった
+}
+
 // ゲーム結果
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GameResult {
     pub matching_id: Uuid,
-    pub winner_id: String,
-    pub loser_id: String,
-    pub player_a_id: String,
-    pub player_b_id: String,
+    pub game_id: Uuid, // 試合ごとのID（再戦では変わる。リプレイ・試合記録のID）
+    pub outcome: GameOutcome,
+    pub winner_id: Option<String>,    // 引き分け・チーム戦の場合はNone
+    pub winning_team: Option<Team>,   // チーム戦で勝利したチーム（引き分け・チーム戦以外はNone）
+    pub loser_id: Option<String>,     // 最下位のプレイヤー（引き分けの場合はNone）
+    pub player_ids: Vec<String>,      // 参加者（参加順）
+    pub placements: Vec<String>,      // 最終順位（1位が先頭）
+    pub end_reason: EndReason,        // 最終ラウンドの終了理由
+    pub scores: HashMap<String, u32>, // ラウンド勝利数 (player_id -> 勝利数)
     pub play_time_seconds: i64,
     pub finished_at: DateTime<Utc>,
 }
 
+// スナップショットのキャラクター差分（前回ACKから変化したフィールドのみ）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CharacterDelta {
+    pub player_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub position: Option<Vector3>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rotation: Option<Vector3>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hp: Option<i32>,
+}
+
+impl CharacterDelta {
+    /// 変化したフィールドがないか
+    pub fn is_empty(&self) -> bool {
+        self.position.is_none() && self.rotation.is_none() && self.hp.is_none()
+    }
+}
+
+// リプレイに記録するイベント
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", content = "data")]
+pub enum ReplayEventKind {
+    RoundStart {
+        round: u32,
+        characters: HashMap<String, Character>, // ラウンド開始時の全員のキャラクター
+    },
+    StateUpdate {
+        player_id: String,
+        position: Vector3, // サーバーが受理した位置（補正後）
+        rotation: Vector3,
+    },
+    Input {
+        player_id: String,
+        action: InputAction,
+    },
+    Attack {
+        attacker_id: String,
+        defender_id: String,
+        attack_type: AttackType,
+        hit: bool,
+        blocked: bool,
+    }, // 近距離攻撃の命中判定
+    Damage {
+        player_id: String, // ダメージを受けたプレイヤー（遠距離攻撃の命中も含む）
+        hp: i32,
+        max_hp: i32,
+        damage: i32,
+        is_critical: bool,
+    },
+    RoundEnd {
+        round: u32,
+        winner_id: Option<String>,
+        winning_team: Option<Team>,
+        end_reason: EndReason,
+        scores: HashMap<String, u32>,
+    },
+}
+
+// 試合開始からの経過時間付きのリプレイイベント
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplayEvent {
+    pub tick: u64,      // 記録時のゲームループのティック番号
+    pub offset_ms: i64, // 試合開始からの経過時間（ms、一時停止中の時間を含む）
+    pub event: ReplayEventKind,
+}
+
 // マッチング情報（一覧表示用）
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MatchingInfo {
@@ -291,6 +651,31 @@ pub struct MatchingInfo {
     pub creator_username: Option<String>,
     pub created_at: DateTime<Utc>,
     pub status: MatchingStatus,
+    pub player_count: usize,           // 現在の参加人数
+    pub max_players: usize,            // 参加人数の上限
+    pub teams: Option<Vec<TeamSlots>>, // チームごとの所属と空き枠（チーム戦以外はNone）
+}
+
+// チームの所属プレイヤーと空き枠
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TeamSlots {
+    pub team: Team,
+    pub player_ids: Vec<String>, // 所属プレイヤー（参加順）
+    pub open_slots: usize,       // 残りの参加枠
+}
+
+impl MatchingInfo {
+    pub fn from_session(session: &MatchingSession) -> Self {
+        Self {
+            matching_id: session.matching_id,
+            creator_username: session.creator_username.clone(),
+            created_at: session.created_at,
+            status: session.status.clone(),
+            player_count: session.players.len(),
+            max_players: session.settings.max_players,
+            teams: session.team_slots(),
+        }
+    }
 }
 
 // WebSocketメッセージ種別
@@ -300,20 +685,51 @@ pub enum WsMessage {
     // クライアント→サーバー
     CreateMatching {
         username: Option<String>,
+        time_limit_seconds: Option<i64>, // 試合時間（省略時はデフォルト）
+        rounds: Option<u32>,             // ラウンド数（省略時はデフォルト）
+        snapshot_interval_ticks: Option<u32>, // スナップショット送信間隔（省略時は送信しない）
+        arena_id: Option<String>,        // アリーナID（省略時はデフォルト）
+        vs_bot: Option<bool>,            // trueの場合はサーバーのボットと対戦
+        bot_difficulty: Option<BotDifficulty>, // ボットの難易度（省略時はnormal）
+        max_players: Option<usize>,      // 参加人数（省略時は2人、チーム戦は4人）
+        team_mode: Option<bool>,         // trueの場合は2チームに分かれて対戦
+        friendly_fire: Option<bool>,     // チーム戦で味方への攻撃を有効にするか（省略時はfalse）
     }, // マッチング作成要求
     JoinMatch {
         matching_id: Uuid,
+        team: Option<Team>, // チーム戦で希望するチーム（省略時は人数の少ないチーム）
     }, // マッチング参加要求
     Ready {
         selected_model_id: String,
     },
     Input {
         action: InputAction,
+        seq: Option<u32>, // シーケンス番号（StateUpdateと共通の連番）
     },
     StateUpdate {
         position: Vector3,
         rotation: Vector3,
+        seq: Option<u32>, // シーケンス番号（Inputと共通の連番）
     },
+    DamageReport {
+        attack_type: AttackType,
+    }, // 被弾報告（ダメージ量はサーバー側で計算）
+    RequestRematch, // 再戦申し込み（バトル終了後の受付期限内）
+    AcceptRematch,  // 再戦承諾
+    Surrender,      // 降参（自分は脱落し、残りが1人になれば試合終了）
+    SnapshotAck {
+        tick: u64,
+    }, // 受信したスナップショットのACK（以降はこのティックからの差分を送信）
+    Spectate {
+        matching_id: Uuid,
+    }, // 観戦要求（以降の入力はすべて拒否される）
+    WatchReplay {
+        game_id: Uuid,      // 再生する試合のID（GameEndの試合結果のgame_id）
+        speed: Option<f32>, // 再生速度（省略時は1.0倍）
+    }, // リプレイ再生要求（以降はリプレイ操作以外の入力を拒否する）
+    SetReplaySpeed {
+        speed: f32,
+    }, // 再生中のリプレイの速度を変更
 
     // サーバー→クライアント
     MatchingCreated {
@@ -325,9 +741,18 @@ pub enum WsMessage {
         current_matchings: Vec<MatchingInfo>, // 現在のマッチング一覧
         timestamp: DateTime<Utc>,
     },
+    PlayerJoined {
+        matching_id: Uuid,
+        player_id: String,   // 参加したプレイヤー
+        team: Option<Team>,  // 参加したプレイヤーのチーム（チーム戦以外はNone）
+        player_count: usize, // 現在の参加人数
+        max_players: usize,  // 参加人数の上限（揃うとMatchingEstablished）
+        timestamp: DateTime<Utc>,
+    },
     MatchingEstablished {
         matching_id: Uuid,
-        opponent_id: String,
+        opponent_id: String,     // 最初の相手（2人対戦との互換用）
+        player_ids: Vec<String>, // 参加者全員（参加順）
         timestamp: DateTime<Utc>,
     },
     MatchingSuccess {
@@ -336,16 +761,64 @@ pub enum WsMessage {
         timestamp: DateTime<Utc>,
     },
     OpponentCharacterSelected {
+        player_id: String, // キャラクターを選択したプレイヤー
         character: Character,
         monster_stats: Option<MonsterStats>, // モンスターステータス情報
         timestamp: DateTime<Utc>,
     },
     GameStart {
-        opponent_character: Character, // 相手のキャラクター情報のみ
-        your_player_id: String,        // 自分のプレイヤーID（識別用）
+        your_character: Character,     // 自分のキャラクター情報（ステータス込み）
+        opponent_character: Character, // 最初の相手のキャラクター情報（2人対戦との互換用）
+        opponent_characters: HashMap<String, Character>, // 相手全員のキャラクター (player_id -> キャラクター)
+        teams: HashMap<String, Team>,                    // 各プレイヤーのチーム（チーム戦以外は空）
+        your_player_id: String,                          // 自分のプレイヤーID（識別用）
+        time_limit_seconds: i64,                         // 1ラウンドの試合時間（秒）
+        rounds: u32,                                     // ラウンド数
+        arena_id: Option<String>,                        // 対戦するアリーナのID
+        timestamp: DateTime<Utc>,
+    },
+    RoundStart {
+        round: u32,                                      // 開始するラウンド（1始まり）
+        your_character: Character,                       // リセット後の自分のキャラクター
+        opponent_character: Character, // リセット後の最初の相手のキャラクター（2人対戦との互換用）
+        opponent_characters: HashMap<String, Character>, // リセット後の相手全員のキャラクター
+        scores: HashMap<String, u32>,  // 現在のラウンド勝利数
+        timestamp: DateTime<Utc>,
+    },
+    RoundEnd {
+        round: u32,
+        winner_id: Option<String>,  // 引き分け・チーム戦の場合はNone
+        winning_team: Option<Team>, // チーム戦で勝利したチーム（引き分け・チーム戦以外はNone）
+        end_reason: EndReason,
+        scores: HashMap<String, u32>, // 更新後のラウンド勝利数
+        timestamp: DateTime<Utc>,
+    },
+    GameResync {
+        your_player_id: String,
+        your_character: Character, // 現在の自分のキャラクター（HP・位置込み）
+        last_processed_seq: Option<u32>, // 最後に処理した自分のシーケンス番号
+        opponent_character: Character, // 現在の最初の相手のキャラクター（2人対戦との互換用）
+        opponent_characters: HashMap<String, Character>, // 現在の相手全員のキャラクター
+        teams: HashMap<String, Team>, // 各プレイヤーのチーム（チーム戦以外は空）
+        round: u32,                // 現在のラウンド
+        rounds: u32,               // ラウンド数
+        scores: HashMap<String, u32>, // 現在のラウンド勝利数
+        time_limit_seconds: i64,   // 1ラウンドの試合時間（秒）
+        elapsed_seconds: i64,      // ゲーム開始からの経過時間（秒）
+        remaining_seconds: i64,    // 現在のラウンドの残り時間（秒）
+        paused: bool,              // 相手の切断などで一時停止中か
+        arena_id: Option<String>,  // 対戦するアリーナのID
+        timestamp: DateTime<Utc>,
+    },
+    GameSnapshot {
+        tick: u64,                       // 単調増加するティック番号
+        baseline_tick: Option<u64>,      // 差分の基準ティック（Noneは全量）
+        characters: Vec<CharacterDelta>, // 変化したキャラクターのみ
+        last_processed_seq: Option<u32>, // 最後に処理した受信者のシーケンス番号
         timestamp: DateTime<Utc>,
     },
     OpponentStateUpdate {
+        player_id: String, // 状態が更新されたプレイヤー
         opponent: Character,
         timestamp: DateTime<Utc>, // サーバー送信時刻（レイテンシ計測用）
     },
@@ -356,8 +829,124 @@ pub enum WsMessage {
         direction: Vector3,
         timestamp: DateTime<Utc>,
     },
+    PositionCorrection {
+        position: Vector3, // サーバーが受理した正規の位置
+        rotation: Vector3,
+        last_processed_seq: Option<u32>, // 補正の対象となったシーケンス番号
+        timestamp: DateTime<Utc>,
+    },
+    AttackResult {
+        attacker_id: String,
+        defender_id: String,
+        attack_type: AttackType,
+        hit: bool,     // サーバーの命中判定
+        blocked: bool, // 障害物に遮られて外れたか
+        timestamp: DateTime<Utc>,
+    },
+    ProjectileSpawned {
+        projectile_id: Uuid,
+        owner_id: String,  // 発射したプレイヤー
+        position: Vector3, // 発射位置
+        velocity: Vector3, // 速度（単位/秒）
+        expires_at: DateTime<Utc>,
+        timestamp: DateTime<Utc>,
+    },
+    ProjectileHit {
+        projectile_id: Uuid,
+        owner_id: String,
+        target_id: String, // 命中したプレイヤー（ダメージはHpUpdateで通知）
+        position: Vector3, // 命中位置
+        timestamp: DateTime<Utc>,
+    },
+    ProjectileExpired {
+        projectile_id: Uuid,
+        position: Vector3, // 消滅位置
+        blocked: bool,     // 障害物に遮られて消滅したか（falseは寿命切れ）
+        timestamp: DateTime<Utc>,
+    },
+    HpUpdate {
+        player_id: String, // ダメージを受けたプレイヤー
+        hp: i32,
+        max_hp: i32,
+        damage: i32,
+        is_critical: bool,
+        timestamp: DateTime<Utc>,
+    },
+    PlayerEliminated {
+        player_id: String, // 降参・切断で脱落したプレイヤー（試合は残りのプレイヤーで続行）
+        end_reason: EndReason, // SurrenderまたはDisconnect
+        timestamp: DateTime<Utc>,
+    },
+    TimeUpdate {
+        remaining_seconds: i64, // 残り時間（秒）
+        timestamp: DateTime<Utc>,
+    },
     GameEnd {
         result: GameResult,
+        rematch_deadline: DateTime<Utc>, // 再戦の受付期限
+        timestamp: DateTime<Utc>,
+    },
+    OpponentDisconnected {
+        player_id: String,      // 切断したプレイヤー
+        remaining_seconds: i64, // 再接続の猶予（秒）。0になると不戦敗
+        reconnect_deadline: DateTime<Utc>,
+        timestamp: DateTime<Utc>,
+    },
+    OpponentReconnected {
+        player_id: String, // 再接続したプレイヤー（ゲーム再開）
+        timestamp: DateTime<Utc>,
+    },
+    RematchRequested {
+        requester_id: String,
+        rematch_deadline: DateTime<Utc>,
+        timestamp: DateTime<Utc>,
+    },
+    RematchAccepted {
+        matching_id: Uuid, // 同じマッチングIDで再度Readyから開始
+        timestamp: DateTime<Utc>,
+    },
+    SpectateStarted {
+        matching_id: Uuid,
+        status: MatchingStatus, // 観戦開始時のマッチング状態
+        spectator_count: usize, // 自分を含む現在の観戦者数
+        timestamp: DateTime<Utc>,
+    },
+    SpectatorSync {
+        matching_id: Uuid,
+        player_ids: Vec<String>,                // 参加者全員（参加順）
+        characters: HashMap<String, Character>, // 全員のキャラクター (player_id -> キャラクター)
+        teams: HashMap<String, Team>,           // 各プレイヤーのチーム（チーム戦以外は空）
+        round: u32,                             // 現在のラウンド
+        rounds: u32,                            // ラウンド数
+        scores: HashMap<String, u32>,           // 現在のラウンド勝利数
+        time_limit_seconds: i64,                // 1ラウンドの試合時間（秒）
+        remaining_seconds: i64,                 // 現在のラウンドの残り時間（秒）
+        paused: bool,                           // 切断などで一時停止中か
+        arena_id: Option<String>,               // 対戦するアリーナのID
+        timestamp: DateTime<Utc>,
+    }, // 観戦者向けのGameStart/RoundStart（観戦開始時にも送信）
+    ReplayStarted {
+        matching_id: Uuid,
+        game_id: Uuid,
+        player_ids: Vec<String>,      // 参加者全員（参加順）
+        teams: HashMap<String, Team>, // 各プレイヤーのチーム（チーム戦以外は空）
+        arena_id: Option<String>,
+        rounds: u32,
+        time_limit_seconds: i64,
+        duration_ms: i64,   // 記録された試合の長さ（ms）
+        event_count: usize, // 記録されたイベント数
+        speed: f32,         // 再生速度
+        timestamp: DateTime<Utc>,
+    },
+    ReplayFrame {
+        tick: u64,
+        offset_ms: i64, // 試合開始からの経過時間（ms）
+        event: ReplayEventKind,
+        timestamp: DateTime<Utc>,
+    },
+    ReplayFinished {
+        matching_id: Uuid,
+        result: Option<GameResult>, // 記録された試合結果
         timestamp: DateTime<Utc>,
     },
 
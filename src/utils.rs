@@ -9,6 +9,15 @@ pub fn add_vector3(a: &Vector3, b: &Vector3) -> Vector3 {
     }
 }
 
+/// 3Dベクトルの減算
+pub fn subtract_vector3(a: &Vector3, b: &Vector3) -> Vector3 {
+    Vector3 {
+        x: a.x - b.x,
+        y: a.y - b.y,
+        z: a.z - b.z,
+    }
+}
+
 /// 3Dベクトルのスカラー乗算
 pub fn multiply_vector3(v: &Vector3, scalar: f32) -> Vector3 {
     Vector3 {
@@ -18,6 +27,11 @@ pub fn multiply_vector3(v: &Vector3, scalar: f32) -> Vector3 {
     }
 }
 
+/// 3Dベクトルの内積
+pub fn dot_vector3(a: &Vector3, b: &Vector3) -> f32 {
+    a.x * b.x + a.y * b.y + a.z * b.z
+}
+
 /// 3Dベクトルの長さ
 pub fn vector3_length(v: &Vector3) -> f32 {
     (v.x * v.x + v.y * v.y + v.z * v.z).sqrt()
@@ -38,7 +52,6 @@ pub fn normalize_vector3(v: &Vector3) -> Vector3 {
 }
 
 /// 2点間の距離
-#[allow(dead_code)]
 pub fn distance(a: &Vector3, b: &Vector3) -> f32 {
     let dx = b.x - a.x;
     let dy = b.y - a.y;
@@ -0,0 +1,190 @@
+mod common;
+
+use chrono::Duration;
+use common::test_stats;
+use uuid::Uuid;
+use webscoket_realtime_prac::game::arena::{
+    ARENA_DATA_DIR, Arena, ArenaBounds, ArenaRegistry, Obstacle, SpawnPoint,
+};
+use webscoket_realtime_prac::game::projectile::ProjectileEvent;
+use webscoket_realtime_prac::game::state::{GameStateManager, MoveValidation};
+use webscoket_realtime_prac::models::{
+    AttackType, Character, DEFAULT_ARENA_ID, MAX_PLAYERS, Vector3,
+};
+
+/// 最初の2つ以外の初期配置はx軸上に並べる
+fn test_arena() -> Arena {
+    let mut arena = Arena {
+        id: "test".to_string(),
+        name: "Test Arena".to_string(),
+        bounds: ArenaBounds {
+            min: Vector3::new(-10.0, 0.0, -10.0),
+            max: Vector3::new(10.0, 5.0, 10.0),
+        },
+        spawn_points: vec![
+            SpawnPoint {
+                position: Vector3::new(0.0, 0.0, -5.0),
+                rotation: Vector3::zero(),
+            },
+            SpawnPoint {
+                position: Vector3::new(0.0, 0.0, 5.0),
+                rotation: Vector3::new(0.0, 180.0, 0.0),
+            },
+        ],
+        obstacles: Vec::new(),
+    };
+    arena
+        .spawn_points
+        .extend((2..MAX_PLAYERS).map(|index| SpawnPoint {
+            position: Vector3::new(index as f32 * 2.0 - 9.0, 0.0, 0.0),
+            rotation: Vector3::zero(),
+        }));
+    arena
+}
+
+fn test_game(arena: Arena) -> GameStateManager {
+    GameStateManager::new(
+        Uuid::new_v4(),
+        vec![
+            (
+                "player_a".to_string(),
+                Character::new("model_a".to_string(), test_stats(10, 10, 0)),
+            ),
+            (
+                "player_b".to_string(),
+                Character::new("model_b".to_string(), test_stats(10, 10, 0)),
+            ),
+        ],
+    )
+    .with_arena(Some(arena))
+}
+
+/// z = 1.0〜1.2 に立つ壁のあるアリーナ
+fn walled_arena() -> Arena {
+    let mut arena = test_arena();
+    arena.obstacles.push(Obstacle::Box {
+        min: Vector3::new(-3.0, 0.0, 1.0),
+        max: Vector3::new(3.0, 3.0, 1.2),
+    });
+    arena
+}
+
+#[test]
+fn test_load_arena_data_files() {
+    let registry = ArenaRegistry::load_dir(ARENA_DATA_DIR).expect("arena data should load");
+
+    // デフォルトのアリーナが含まれ、すべて最大人数分の初期配置を持つ
+    assert!(registry.get(DEFAULT_ARENA_ID).is_some());
+    for id in registry.ids() {
+        let arena = registry.get(id).unwrap();
+        assert!(arena.has_spawn_points_for(MAX_PLAYERS), "arena {}", id);
+        assert!(arena.validate().is_ok(), "arena {}", id);
+    }
+}
+
+#[test]
+fn test_arena_validation() {
+    assert!(test_arena().validate().is_ok());
+
+    // 初期配置が最大人数より少ない
+    let mut arena = test_arena();
+    arena.spawn_points.truncate(MAX_PLAYERS - 1);
+    assert!(arena.validate().is_err());
+    assert!(arena.has_spawn_points_for(MAX_PLAYERS - 1));
+    assert!(!arena.has_spawn_points_for(MAX_PLAYERS));
+
+    // 初期配置が範囲外
+    let mut arena = test_arena();
+    arena.spawn_points[1].position = Vector3::new(0.0, 0.0, 50.0);
+    assert!(arena.validate().is_err());
+
+    // IDの重複は登録できない
+    let mut registry = ArenaRegistry::default();
+    assert!(registry.insert(test_arena()).is_ok());
+    assert!(registry.insert(test_arena()).is_err());
+}
+
+#[test]
+fn test_game_uses_spawn_points_and_bounds() {
+    let mut game = test_game(test_arena());
+    let start = game.started_at;
+
+    // 参加順に初期配置に置かれる
+    assert_eq!(
+        game.characters["player_a"].position,
+        Vector3::new(0.0, 0.0, -5.0)
+    );
+    assert_eq!(
+        game.characters["player_b"].position,
+        Vector3::new(0.0, 0.0, 5.0)
+    );
+    assert_eq!(
+        game.characters["player_b"].rotation,
+        Vector3::new(0.0, 180.0, 0.0)
+    );
+
+    // 範囲外への移動は境界に補正される
+    let result = game.update_state(
+        "player_b",
+        Vector3::new(0.0, 0.0, 12.0),
+        Vector3::zero(),
+        start + Duration::seconds(1),
+    );
+    match result {
+        Some(MoveValidation::Corrected(position)) => {
+            assert_eq!(position, Vector3::new(0.0, 0.0, 10.0));
+        }
+        other => panic!("expected correction, got {:?}", other),
+    }
+    assert_eq!(game.characters["player_b"].position.z, 10.0);
+}
+
+#[test]
+fn test_attack_blocked_by_obstacle() {
+    let forward = Vector3::new(0.0, 0.0, 1.0);
+
+    // 障害物がなければ射程内の攻撃は命中
+    let mut game = test_game(test_arena());
+    let now = game.started_at;
+    game.character_mut("player_a").unwrap().position = Vector3::zero();
+    game.character_mut("player_b").unwrap().position = Vector3::new(0.0, 0.0, 2.5);
+    let judgement = game
+        .judge_attack("player_a", &AttackType::Normal, &forward, None, now)
+        .unwrap();
+    assert!(judgement.hit);
+    assert!(!judgement.blocked);
+
+    // 壁越しの攻撃は外れとして報告される
+    let mut game = test_game(walled_arena());
+    game.character_mut("player_a").unwrap().position = Vector3::zero();
+    game.character_mut("player_b").unwrap().position = Vector3::new(0.0, 0.0, 2.5);
+    let judgement = game
+        .judge_attack("player_a", &AttackType::Normal, &forward, None, now)
+        .unwrap();
+    assert!(!judgement.hit);
+    assert!(judgement.blocked);
+    assert!(
+        game.take_confirmed_hit("player_b", &AttackType::Normal, now)
+            .is_none()
+    );
+}
+
+#[test]
+fn test_projectile_blocked_by_obstacle() {
+    let mut game = test_game(walled_arena());
+    let start = game.started_at;
+    game.character_mut("player_a").unwrap().position = Vector3::zero();
+    game.character_mut("player_b").unwrap().position = Vector3::new(0.0, 0.0, 5.0);
+
+    game.spawn_projectile("player_a", &Vector3::new(0.0, 0.0, 1.0), start)
+        .unwrap();
+
+    // 100ms後に z = 2.0 まで進む途中で壁に当たって消滅する
+    let events = game.update_projectiles(start + Duration::milliseconds(100));
+    assert!(matches!(
+        events.as_slice(),
+        [ProjectileEvent::Blocked { .. }]
+    ));
+    assert!(game.projectiles.is_empty());
+    assert_eq!(game.characters["player_b"].hp, 100);
+}
@@ -0,0 +1,237 @@
+mod common;
+
+use actix::Actor;
+use chrono::{Duration, Utc};
+use common::test_stats;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tokio::sync::mpsc;
+use uuid::Uuid;
+use webscoket_realtime_prac::game::bot::{BotAction, BotBrain, BotPlayer, BotProfile};
+use webscoket_realtime_prac::game::manager::{GameManager, ProcessInput, StartGame};
+use webscoket_realtime_prac::game::state::GameStateManager;
+use webscoket_realtime_prac::models::{
+    AttackType, BotDifficulty, Character, InputAction, MatchingSession, MatchingStatus,
+    MonsterStats, PlayerInput, Vector3, WsMessage,
+};
+
+/// 原点のボットと `opponent_position` の相手でゲームを開始した状態のボット
+fn started_brain(stats: MonsterStats, opponent_position: Vector3) -> BotBrain {
+    let mut brain = BotBrain::new("bot".to_string(), BotDifficulty::Hard);
+    let mut opponent = Character::new("model_a".to_string(), test_stats(10, 10, 0));
+    opponent.position = opponent_position;
+    brain.observe(&WsMessage::GameStart {
+        your_character: Character::new("model_b".to_string(), stats),
+        opponent_character: opponent.clone(),
+        opponent_characters: HashMap::from([("player_a".to_string(), opponent)]),
+        teams: HashMap::new(),
+        your_player_id: "bot".to_string(),
+        time_limit_seconds: 180,
+        rounds: 1,
+        arena_id: None,
+        timestamp: Utc::now(),
+    });
+    brain
+}
+
+#[test]
+fn test_profile_scales_with_difficulty() {
+    let easy = BotProfile::for_difficulty(BotDifficulty::Easy);
+    let hard = BotProfile::for_difficulty(BotDifficulty::Hard);
+
+    assert!(easy.think_interval_ms > hard.think_interval_ms);
+    assert!(easy.aim_error_deg > hard.aim_error_deg);
+    assert!(easy.speed_ratio < hard.speed_ratio);
+    assert!(easy.attack_chance < hard.attack_chance);
+}
+
+#[test]
+fn test_bot_idle_before_game_start() {
+    let mut brain = BotBrain::new("bot".to_string(), BotDifficulty::Normal);
+    assert!(brain.decide_with_roll(Utc::now(), 0.5, 0.0).is_empty());
+}
+
+#[test]
+fn test_melee_bot_approaches_and_attacks() {
+    let now = Utc::now();
+    let mut brain = started_brain(test_stats(10, 0, 0), Vector3::new(0.0, 0.0, 10.0));
+
+    // 遠い相手には向き直って近づく（Hardは100msで1.0進む）。遠距離攻撃がないので攻撃しない
+    let actions = brain.decide_with_roll(now, 0.5, 0.0);
+    match actions.as_slice() {
+        [BotAction::Move { position, rotation }] => {
+            assert!((position.z - 1.0).abs() < 1e-4);
+            assert!(rotation.y.abs() < 1e-4);
+        }
+        other => panic!("expected a single move, got {:?}", other),
+    }
+
+    // 射程内に入ると相手の方向へ近距離攻撃する
+    brain.observe(&WsMessage::PositionCorrection {
+        position: Vector3::new(0.0, 0.0, 8.5),
+        rotation: Vector3::zero(),
+        last_processed_seq: None,
+        timestamp: now,
+    });
+    let actions = brain.decide_with_roll(now, 0.5, 0.0);
+    match actions.as_slice() {
+        [
+            BotAction::Move { .. },
+            BotAction::Attack {
+                attack_type,
+                direction,
+                ..
+            },
+        ] => {
+            assert!(matches!(attack_type, AttackType::Normal));
+            assert!((direction.z - 1.0).abs() < 1e-4);
+        }
+        other => panic!("expected move and attack, got {:?}", other),
+    }
+
+    // クールダウン中は攻撃しない
+    let actions = brain.decide_with_roll(now + Duration::milliseconds(500), 0.5, 0.0);
+    assert_eq!(actions.len(), 1);
+    let actions = brain.decide_with_roll(now + Duration::milliseconds(1000), 0.5, 0.0);
+    assert_eq!(actions.len(), 2);
+}
+
+#[test]
+fn test_ranged_bot_keeps_distance_and_shoots() {
+    let now = Utc::now();
+    let mut brain = started_brain(test_stats(0, 10, 0), Vector3::new(0.0, 0.0, 3.0));
+
+    // 近すぎる相手からは離れつつ遠距離攻撃する
+    let actions = brain.decide_with_roll(now, 0.5, 0.0);
+    match actions.as_slice() {
+        [
+            BotAction::Move { position, .. },
+            BotAction::Attack { attack_type, .. },
+        ] => {
+            assert!(position.z < 0.0);
+            assert!(matches!(attack_type, AttackType::Special));
+        }
+        other => panic!("expected move and attack, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_bot_idle_while_paused_or_opponent_down() {
+    let now = Utc::now();
+    let mut brain = started_brain(test_stats(10, 10, 0), Vector3::new(0.0, 0.0, 10.0));
+
+    // 相手の切断中は行動しない
+    brain.observe(&WsMessage::OpponentDisconnected {
+        player_id: "player_a".to_string(),
+        remaining_seconds: 30,
+        reconnect_deadline: now + Duration::seconds(30),
+        timestamp: now,
+    });
+    assert!(brain.decide_with_roll(now, 0.5, 0.0).is_empty());
+
+    brain.observe(&WsMessage::OpponentReconnected {
+        player_id: "player_a".to_string(),
+        timestamp: now,
+    });
+    assert!(!brain.decide_with_roll(now, 0.5, 0.0).is_empty());
+
+    // 相手のHPが0になったら攻撃しない
+    brain.observe(&WsMessage::HpUpdate {
+        player_id: "player_a".to_string(),
+        hp: 0,
+        max_hp: 100,
+        damage: 100,
+        is_critical: false,
+        timestamp: now,
+    });
+    assert!(brain.decide_with_roll(now, 0.5, 0.0).is_empty());
+    assert!(!brain.is_finished());
+}
+
+#[test]
+fn test_bot_reports_only_its_own_confirmed_hits() {
+    let now = Utc::now();
+    let mut brain = started_brain(test_stats(10, 10, 0), Vector3::new(0.0, 0.0, 10.0));
+    let attack_result = |defender_id: &str, hit: bool| WsMessage::AttackResult {
+        attacker_id: "player_a".to_string(),
+        defender_id: defender_id.to_string(),
+        attack_type: AttackType::Normal,
+        hit,
+        blocked: false,
+        timestamp: now,
+    };
+
+    // 外れた攻撃や他プレイヤーへの命中は報告しない
+    brain.observe(&attack_result("bot", false));
+    brain.observe(&attack_result("player_a", true));
+    assert!(brain.take_damage_reports().is_empty());
+
+    brain.observe(&attack_result("bot", true));
+    assert_eq!(brain.take_damage_reports(), vec![AttackType::Normal]);
+    assert!(brain.take_damage_reports().is_empty());
+}
+
+#[actix_rt::test]
+async fn test_human_melee_hit_lowers_bot_hp() {
+    let matching_id = Uuid::new_v4();
+    let mut session = MatchingSession::new_with_username("player_a".to_string(), None);
+    session.matching_id = matching_id;
+    session.status = MatchingStatus::InGame;
+    let sessions = Arc::new(Mutex::new(HashMap::from([(matching_id, session)])));
+    let game_manager = GameManager::new(sessions.clone()).start();
+
+    // ボットの正面・射程内に人間のプレイヤーがいる
+    let mut human = Character::new("model_a".to_string(), test_stats(10, 10, 0));
+    human.position = Vector3::new(0.0, 0.0, 2.5);
+    let game = GameStateManager::new(
+        matching_id,
+        vec![
+            ("player_a".to_string(), human),
+            (
+                "bot".to_string(),
+                Character::new("model_b".to_string(), test_stats(10, 10, 0)),
+            ),
+        ],
+    );
+    let (tx_human, mut rx_human) = mpsc::unbounded_channel();
+    let (tx_bot, rx_bot) = mpsc::unbounded_channel();
+    game_manager
+        .send(StartGame {
+            game,
+            ws_senders: HashMap::from([
+                ("player_a".to_string(), tx_human),
+                ("bot".to_string(), tx_bot),
+            ]),
+        })
+        .await
+        .unwrap();
+    let brain = BotBrain::new("bot".to_string(), BotDifficulty::Hard);
+    let think_interval_ms = brain.profile.think_interval_ms;
+    BotPlayer::new(matching_id, brain, rx_bot, game_manager.clone(), sessions).start();
+
+    // 人間の近距離攻撃が命中すると、ボットが被弾報告してHPが減る
+    game_manager
+        .send(ProcessInput {
+            matching_id,
+            input: PlayerInput {
+                player_id: "player_a".to_string(),
+                action: InputAction::Attack {
+                    attack_type: AttackType::Normal,
+                    position: Vector3::new(0.0, 0.0, 2.5),
+                    direction: Vector3::new(0.0, 0.0, -1.0),
+                    view_time: None,
+                },
+                seq: None,
+                timestamp: Utc::now(),
+            },
+        })
+        .await
+        .unwrap();
+    actix_rt::time::sleep(std::time::Duration::from_millis(think_interval_ms * 3)).await;
+
+    let bot_hp = std::iter::from_fn(|| rx_human.try_recv().ok()).find_map(|msg| match msg {
+        WsMessage::HpUpdate { player_id, hp, .. } if player_id == "bot" => Some(hp),
+        _ => None,
+    });
+    assert!(matches!(bot_hp, Some(hp) if hp < 100), "got {:?}", bot_hp);
+}
@@ -0,0 +1,16 @@
+use webscoket_realtime_prac::models::MonsterStats;
+
+/// テスト用のモンスターステータス（攻撃力・防御力以外は共通）
+pub fn test_stats(short_power: i64, long_power: i64, defense: i64) -> MonsterStats {
+    MonsterStats {
+        name: "Test Monster".to_string(),
+        max_hp: 100,
+        short_range_attack_power: short_power,
+        long_range_attack_power: long_power,
+        defense_power: defense,
+        move_speed: 10,
+        attack_range: 2,
+        attack_cooldown: 1000,
+        size_type: "Medium".to_string(),
+    }
+}
@@ -0,0 +1,619 @@
+mod common;
+
+use actix::Actor;
+use common::test_stats;
+use sqlx::sqlite::SqlitePoolOptions;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tokio::sync::mpsc;
+use uuid::Uuid;
+use webscoket_realtime_prac::db::models::MatchRecord;
+use webscoket_realtime_prac::game::manager::{
+    AddSpectator, ApplyDamage, GameManager, PlayerDisconnected, ProcessInput, ProcessStateUpdate,
+    RebindPlayer, RemoveSpectator, StartGame, Surrender,
+};
+use webscoket_realtime_prac::game::replay::{Replay, ReplayStore};
+use webscoket_realtime_prac::game::state::GameStateManager;
+use webscoket_realtime_prac::handlers::{MatchingSessions, Replays};
+use webscoket_realtime_prac::models::{
+    AttackType, Character, EndReason, InputAction, MAX_SPECTATORS_PER_MATCH, MatchingSession,
+    MatchingStatus, PlayerInput, ReplayEventKind, Vector3, WsMessage,
+};
+
+#[actix_rt::test]
+async fn test_rebind_player_sends_resync_to_new_channel() {
+    let matching_sessions: MatchingSessions = Arc::new(Mutex::new(HashMap::new()));
+    let game_manager = GameManager::new(matching_sessions).start();
+
+    let matching_id = Uuid::new_v4();
+    let mut game = GameStateManager::new(
+        matching_id,
+        vec![
+            (
+                "player_a".to_string(),
+                Character::new("model_a".to_string(), test_stats(10, 10, 5)),
+            ),
+            (
+                "player_b".to_string(),
+                Character::new("model_b".to_string(), test_stats(10, 10, 5)),
+            ),
+        ],
+    )
+    .with_rounds(3);
+    game.character_mut("player_b").unwrap().hp = 55;
+
+    let (tx_a, _rx_a) = mpsc::unbounded_channel();
+    let (tx_b, _rx_b) = mpsc::unbounded_channel();
+    let ws_senders = HashMap::from([
+        ("player_a".to_string(), tx_a),
+        ("player_b".to_string(), tx_b),
+    ]);
+    game_manager
+        .send(StartGame { game, ws_senders })
+        .await
+        .unwrap();
+
+    // player_bが新しいチャンネルで再接続
+    let (new_tx_b, mut new_rx_b) = mpsc::unbounded_channel();
+    game_manager
+        .send(RebindPlayer {
+            matching_id,
+            player_id: "player_b".to_string(),
+            sender: new_tx_b,
+        })
+        .await
+        .unwrap();
+
+    match new_rx_b.try_recv() {
+        Ok(WsMessage::GameResync {
+            your_player_id,
+            your_character,
+            opponent_character,
+            round,
+            rounds,
+            paused,
+            ..
+        }) => {
+            assert_eq!(your_player_id, "player_b");
+            assert_eq!(your_character.hp, 55);
+            assert_eq!(opponent_character.model_id, "model_a");
+            assert_eq!(round, 1);
+            assert_eq!(rounds, 3);
+            assert!(!paused);
+        }
+        other => panic!("expected GameResync, got {:?}", other),
+    }
+}
+
+/// ゲーム中のマッチングを登録した共有セッション
+fn in_game_sessions(matching_id: Uuid) -> MatchingSessions {
+    let mut session = MatchingSession::new_with_username("player_a".to_string(), None);
+    session.matching_id = matching_id;
+    session.status = MatchingStatus::InGame;
+    Arc::new(Mutex::new(HashMap::from([(matching_id, session)])))
+}
+
+fn two_player_game(matching_id: Uuid) -> GameStateManager {
+    GameStateManager::new(
+        matching_id,
+        vec![
+            (
+                "player_a".to_string(),
+                Character::new("model_a".to_string(), test_stats(10, 10, 5)),
+            ),
+            (
+                "player_b".to_string(),
+                Character::new("model_b".to_string(), test_stats(10, 10, 5)),
+            ),
+        ],
+    )
+}
+
+/// 受信済みのメッセージをすべて取り出す
+fn drain(rx: &mut mpsc::UnboundedReceiver<WsMessage>) -> Vec<WsMessage> {
+    std::iter::from_fn(|| rx.try_recv().ok()).collect()
+}
+
+#[actix_rt::test]
+async fn test_spectator_receives_game_events_and_cannot_play() {
+    let matching_id = Uuid::new_v4();
+    let game_manager = GameManager::new(in_game_sessions(matching_id)).start();
+
+    let (tx_a, _rx_a) = mpsc::unbounded_channel();
+    let (tx_b, mut rx_b) = mpsc::unbounded_channel();
+    let ws_senders = HashMap::from([
+        ("player_a".to_string(), tx_a),
+        ("player_b".to_string(), tx_b),
+    ]);
+    game_manager
+        .send(StartGame {
+            game: two_player_game(matching_id),
+            ws_senders,
+        })
+        .await
+        .unwrap();
+
+    // 試合中に観戦を開始すると、全員分の現在の状態が届く
+    let (tx_spectator, mut rx_spectator) = mpsc::unbounded_channel();
+    let spectator_count = game_manager
+        .send(AddSpectator {
+            matching_id,
+            spectator_id: Uuid::new_v4(),
+            sender: tx_spectator,
+        })
+        .await
+        .unwrap();
+    assert_eq!(spectator_count, Ok(1));
+    match drain(&mut rx_spectator).as_slice() {
+        [
+            WsMessage::SpectateStarted {
+                status,
+                spectator_count,
+                ..
+            },
+            WsMessage::SpectatorSync {
+                player_ids,
+                characters,
+                ..
+            },
+        ] => {
+            assert_eq!(*status, MatchingStatus::InGame);
+            assert_eq!(*spectator_count, 1);
+            assert_eq!(player_ids, &["player_a", "player_b"]);
+            assert_eq!(characters.len(), 2);
+        }
+        other => panic!(
+            "expected SpectateStarted and SpectatorSync, got {:?}",
+            other
+        ),
+    }
+
+    // 両プレイヤーの状態更新と攻撃が観戦者にも届く
+    game_manager
+        .send(ProcessStateUpdate {
+            matching_id,
+            player_id: "player_a".to_string(),
+            position: Vector3::new(0.5, 0.0, 0.0),
+            rotation: Vector3::zero(),
+            seq: None,
+        })
+        .await
+        .unwrap();
+    game_manager
+        .send(ProcessInput {
+            matching_id,
+            input: PlayerInput {
+                player_id: "player_b".to_string(),
+                action: InputAction::Attack {
+                    attack_type: AttackType::Normal,
+                    position: Vector3::zero(),
+                    direction: Vector3::new(1.0, 0.0, 0.0),
+                    view_time: None,
+                },
+                seq: None,
+                timestamp: chrono::Utc::now(),
+            },
+        })
+        .await
+        .unwrap();
+    let received = drain(&mut rx_spectator);
+    assert!(received.iter().any(|msg| matches!(
+        msg,
+        WsMessage::OpponentStateUpdate { player_id, .. } if player_id == "player_a"
+    )));
+    assert!(received.iter().any(|msg| matches!(
+        msg,
+        WsMessage::OpponentAttacked { attacker_id, .. } if attacker_id == "player_b"
+    )));
+
+    // 観戦者の送信チャンネルはプレイヤー宛ての通知に混ざらない
+    assert!(
+        drain(&mut rx_b)
+            .iter()
+            .all(|msg| !matches!(msg, WsMessage::SpectatorSync { .. }))
+    );
+
+    // 試合終了も観戦者に届く
+    game_manager
+        .send(Surrender {
+            matching_id,
+            player_id: "player_a".to_string(),
+        })
+        .await
+        .unwrap();
+    assert!(
+        drain(&mut rx_spectator)
+            .iter()
+            .any(|msg| matches!(msg, WsMessage::GameEnd { .. }))
+    );
+
+    // 試合終了後は観戦者の送信チャンネルが解放される
+    assert!(matches!(
+        rx_spectator.try_recv(),
+        Err(mpsc::error::TryRecvError::Disconnected)
+    ));
+}
+
+#[actix_rt::test]
+async fn test_spectator_limit_per_match() {
+    let matching_id = Uuid::new_v4();
+    let game_manager = GameManager::new(in_game_sessions(matching_id)).start();
+
+    // 存在しないマッチングは観戦できない
+    let (tx, _rx) = mpsc::unbounded_channel();
+    let result = game_manager
+        .send(AddSpectator {
+            matching_id: Uuid::new_v4(),
+            spectator_id: Uuid::new_v4(),
+            sender: tx.clone(),
+        })
+        .await
+        .unwrap();
+    assert!(result.is_err());
+
+    let mut spectator_ids = Vec::new();
+    for expected in 1..=MAX_SPECTATORS_PER_MATCH {
+        let spectator_id = Uuid::new_v4();
+        let result = game_manager
+            .send(AddSpectator {
+                matching_id,
+                spectator_id,
+                sender: tx.clone(),
+            })
+            .await
+            .unwrap();
+        assert_eq!(result, Ok(expected));
+        spectator_ids.push(spectator_id);
+    }
+
+    // 上限に達したら拒否
+    let result = game_manager
+        .send(AddSpectator {
+            matching_id,
+            spectator_id: Uuid::new_v4(),
+            sender: tx.clone(),
+        })
+        .await
+        .unwrap();
+    assert!(result.is_err());
+
+    // 誰かが退出すれば再び観戦できる
+    game_manager
+        .send(RemoveSpectator {
+            matching_id,
+            spectator_id: spectator_ids[0],
+        })
+        .await
+        .unwrap();
+    let result = game_manager
+        .send(AddSpectator {
+            matching_id,
+            spectator_id: Uuid::new_v4(),
+            sender: tx,
+        })
+        .await
+        .unwrap();
+    assert_eq!(result, Ok(MAX_SPECTATORS_PER_MATCH));
+}
+
+#[actix_rt::test]
+async fn test_replay_is_saved_when_game_ends() {
+    let matching_id = Uuid::new_v4();
+    let dir = std::env::temp_dir().join(format!("test_replays_{}", Uuid::new_v4()));
+    let replays: Replays = Arc::new(ReplayStore::new(&dir));
+    let game_manager = GameManager::new(in_game_sessions(matching_id))
+        .with_replays(replays.clone())
+        .start();
+
+    let (tx_a, _rx_a) = mpsc::unbounded_channel();
+    let (tx_b, _rx_b) = mpsc::unbounded_channel();
+    let ws_senders = HashMap::from([
+        ("player_a".to_string(), tx_a),
+        ("player_b".to_string(), tx_b),
+    ]);
+    let game = two_player_game(matching_id);
+    let game_id = game.game_id;
+    game_manager
+        .send(StartGame {
+            game,
+            ws_senders: ws_senders.clone(),
+        })
+        .await
+        .unwrap();
+    game_manager
+        .send(ProcessStateUpdate {
+            matching_id,
+            player_id: "player_a".to_string(),
+            position: Vector3::new(0.5, 0.0, 0.0),
+            rotation: Vector3::zero(),
+            seq: None,
+        })
+        .await
+        .unwrap();
+    game_manager
+        .send(Surrender {
+            matching_id,
+            player_id: "player_b".to_string(),
+        })
+        .await
+        .unwrap();
+
+    let replay = wait_for_replay(&replays, &game_id)
+        .await
+        .expect("replay should be saved after the game ends");
+
+    assert_eq!(replay.game_id, game_id);
+    assert_eq!(replay.player_ids, vec!["player_a", "player_b"]);
+    assert!(matches!(
+        replay.events.first().map(|event| &event.event),
+        Some(ReplayEventKind::RoundStart { round: 1, .. })
+    ));
+    assert!(replay.events.iter().any(|event| matches!(
+        &event.event,
+        ReplayEventKind::StateUpdate { player_id, .. } if player_id == "player_a"
+    )));
+    let result = replay.result.expect("replay should include the result");
+    assert_eq!(result.winner_id, Some("player_a".to_string()));
+    assert_eq!(result.end_reason, EndReason::Surrender);
+
+    // 同じマッチングでの再戦は別の試合として保存され、前の試合のリプレイは残る
+    let rematch = two_player_game(matching_id);
+    let rematch_id = rematch.game_id;
+    assert_ne!(rematch_id, game_id);
+    game_manager
+        .send(StartGame {
+            game: rematch,
+            ws_senders,
+        })
+        .await
+        .unwrap();
+    game_manager
+        .send(Surrender {
+            matching_id,
+            player_id: "player_a".to_string(),
+        })
+        .await
+        .unwrap();
+    let rematch_replay = wait_for_replay(&replays, &rematch_id)
+        .await
+        .expect("rematch replay should be saved");
+    assert_eq!(
+        rematch_replay.result.unwrap().winner_id,
+        Some("player_b".to_string())
+    );
+    let first_replay = replays.load(&game_id).await.unwrap().unwrap();
+    assert_eq!(
+        first_replay.result.unwrap().winner_id,
+        Some("player_a".to_string())
+    );
+
+    let _ = std::fs::remove_dir_all(dir);
+}
+
+/// 保存は非同期で行われるため、ファイルができるまで待って読み込む
+async fn wait_for_replay(replays: &Replays, game_id: &Uuid) -> Option<Replay> {
+    for _ in 0..50 {
+        if let Some(replay) = replays.load(game_id).await.unwrap() {
+            return Some(replay);
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    }
+    None
+}
+
+#[actix_rt::test]
+async fn test_match_record_is_saved_when_game_ends() {
+    let db_path = std::env::temp_dir().join(format!("test_manager_{}.db", Uuid::new_v4()));
+    let pool = SqlitePoolOptions::new()
+        .max_connections(5)
+        .connect(&format!("sqlite:{}?mode=rwc", db_path.to_str().unwrap()))
+        .await
+        .expect("Failed to create test database pool");
+    sqlx::migrate!("./migrations")
+        .run(&pool)
+        .await
+        .expect("Failed to run migrations");
+
+    let matching_id = Uuid::new_v4();
+    let game_manager = GameManager::new(in_game_sessions(matching_id))
+        .with_db(pool.clone())
+        .start();
+
+    let (tx_a, _rx_a) = mpsc::unbounded_channel();
+    let (tx_b, _rx_b) = mpsc::unbounded_channel();
+    let ws_senders = HashMap::from([
+        ("player_a".to_string(), tx_a),
+        ("player_b".to_string(), tx_b),
+    ]);
+    let game = two_player_game(matching_id);
+    let game_id = game.game_id;
+    game_manager
+        .send(StartGame { game, ws_senders })
+        .await
+        .unwrap();
+    game_manager
+        .send(Surrender {
+            matching_id,
+            player_id: "player_a".to_string(),
+        })
+        .await
+        .unwrap();
+
+    // 保存は非同期で行われるため、記録されるまで待つ
+    let mut records = Vec::new();
+    for _ in 0..50 {
+        records = MatchRecord::list_by_player(&pool, "player_a", 10)
+            .await
+            .unwrap();
+        if !records.is_empty() {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    }
+    let [record] = records.as_slice() else {
+        panic!("expected one match record, got {:?}", records);
+    };
+
+    // 試合記録のIDはリプレイと同じ試合ID
+    assert_eq!(record.id, game_id.to_string());
+    assert_eq!(record.matching_id, matching_id.to_string());
+    assert_eq!(record.winner_id, Some("player_b".to_string()));
+    assert_eq!(record.end_reason, "Surrender");
+    let players: Vec<(&str, &str, i64)> = record
+        .players
+        .iter()
+        .map(|player| {
+            (
+                player.player_id.as_str(),
+                player.monster_id.as_str(),
+                player.placement,
+            )
+        })
+        .collect();
+    assert_eq!(
+        players,
+        vec![("player_a", "model_a", 2), ("player_b", "model_b", 1)]
+    );
+}
+
+#[actix_rt::test]
+async fn test_damage_report_is_ignored_while_paused() {
+    let matching_id = Uuid::new_v4();
+    let game_manager = GameManager::new(in_game_sessions(matching_id)).start();
+
+    // player_bの正面・射程内にplayer_aがいる
+    let mut game = two_player_game(matching_id);
+    game.character_mut("player_b").unwrap().position = Vector3::new(0.0, 0.0, 2.5);
+    let (tx_a, _rx_a) = mpsc::unbounded_channel();
+    let (tx_b, mut rx_b) = mpsc::unbounded_channel();
+    let ws_senders = HashMap::from([
+        ("player_a".to_string(), tx_a),
+        ("player_b".to_string(), tx_b),
+    ]);
+    game_manager
+        .send(StartGame { game, ws_senders })
+        .await
+        .unwrap();
+
+    // 一時停止前に命中が確定する
+    game_manager
+        .send(ProcessInput {
+            matching_id,
+            input: PlayerInput {
+                player_id: "player_b".to_string(),
+                action: InputAction::Attack {
+                    attack_type: AttackType::Normal,
+                    position: Vector3::new(0.0, 0.0, 2.5),
+                    direction: Vector3::new(0.0, 0.0, -1.0),
+                    view_time: None,
+                },
+                seq: None,
+                timestamp: chrono::Utc::now(),
+            },
+        })
+        .await
+        .unwrap();
+    assert!(drain(&mut rx_b).iter().any(|msg| matches!(
+        msg,
+        WsMessage::AttackResult { hit: true, defender_id, .. } if defender_id == "player_a"
+    )));
+
+    // player_aが切断して一時停止している間の被弾報告は適用されない
+    game_manager
+        .send(PlayerDisconnected {
+            matching_id,
+            player_id: "player_a".to_string(),
+        })
+        .await
+        .unwrap();
+    game_manager
+        .send(ApplyDamage {
+            matching_id,
+            player_id: "player_a".to_string(),
+            attack_type: AttackType::Normal,
+        })
+        .await
+        .unwrap();
+    assert!(
+        drain(&mut rx_b)
+            .iter()
+            .all(|msg| !matches!(msg, WsMessage::HpUpdate { .. }))
+    );
+}
+
+#[actix_rt::test]
+async fn test_input_from_forfeited_player_is_dropped() {
+    let matching_id = Uuid::new_v4();
+    let game_manager = GameManager::new(in_game_sessions(matching_id)).start();
+
+    // player_cの正面・射程内にplayer_aがいる3人対戦
+    let players = ["player_a", "player_b", "player_c"];
+    let mut game = GameStateManager::new(
+        matching_id,
+        players
+            .iter()
+            .map(|id| {
+                let character = Character::new(format!("model_{}", id), test_stats(10, 10, 5));
+                (id.to_string(), character)
+            })
+            .collect(),
+    );
+    game.character_mut("player_c").unwrap().position = Vector3::new(0.0, 0.0, 2.5);
+    let (tx_a, mut rx_a) = mpsc::unbounded_channel();
+    let (tx_b, _rx_b) = mpsc::unbounded_channel();
+    let (tx_c, _rx_c) = mpsc::unbounded_channel();
+    let ws_senders = HashMap::from([
+        ("player_a".to_string(), tx_a),
+        ("player_b".to_string(), tx_b),
+        ("player_c".to_string(), tx_c),
+    ]);
+    game_manager
+        .send(StartGame { game, ws_senders })
+        .await
+        .unwrap();
+
+    // 降参しても残り2人なので試合は続く
+    game_manager
+        .send(Surrender {
+            matching_id,
+            player_id: "player_c".to_string(),
+        })
+        .await
+        .unwrap();
+    drain(&mut rx_a);
+
+    // 脱落したplayer_cの攻撃・移動は処理されない
+    game_manager
+        .send(ProcessInput {
+            matching_id,
+            input: PlayerInput {
+                player_id: "player_c".to_string(),
+                action: InputAction::Attack {
+                    attack_type: AttackType::Normal,
+                    position: Vector3::new(0.0, 0.0, 2.5),
+                    direction: Vector3::new(0.0, 0.0, -1.0),
+                    view_time: None,
+                },
+                seq: None,
+                timestamp: chrono::Utc::now(),
+            },
+        })
+        .await
+        .unwrap();
+    game_manager
+        .send(ProcessStateUpdate {
+            matching_id,
+            player_id: "player_c".to_string(),
+            position: Vector3::new(0.0, 0.0, 2.0),
+            rotation: Vector3::zero(),
+            seq: None,
+        })
+        .await
+        .unwrap();
+    assert!(drain(&mut rx_a).iter().all(|msg| !matches!(
+        msg,
+        WsMessage::AttackResult { .. }
+            | WsMessage::OpponentAttacked { .. }
+            | WsMessage::OpponentStateUpdate { .. }
+    )));
+}
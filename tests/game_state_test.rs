@@ -0,0 +1,988 @@
+mod common;
+
+use chrono::{Duration, Utc};
+use common::test_stats;
+use uuid::Uuid;
+use webscoket_realtime_prac::game::projectile::{
+    PROJECTILE_LIFETIME_MS, PROJECTILE_SPEED, ProjectileEvent,
+};
+use webscoket_realtime_prac::game::snapshot::SnapshotTracker;
+use webscoket_realtime_prac::game::state::{
+    DISCONNECT_GRACE_SECONDS, DamageCalculator, GameStateManager, MAX_REWIND_MS, MoveValidation,
+    RoundProgress,
+};
+use webscoket_realtime_prac::models::{
+    AttackType, Character, EndReason, GameOutcome, GameResult, InputAction, MonsterStats,
+    PlayerInput, Team, Vector3,
+};
+
+fn test_game(a_stats: MonsterStats, b_stats: MonsterStats) -> GameStateManager {
+    GameStateManager::new(
+        Uuid::new_v4(),
+        vec![
+            (
+                "player_a".to_string(),
+                Character::new("model_a".to_string(), a_stats),
+            ),
+            (
+                "player_b".to_string(),
+                Character::new("model_b".to_string(), b_stats),
+            ),
+        ],
+    )
+}
+
+#[test]
+fn test_damage_uses_attack_type_power_and_defense() {
+    let calculator = DamageCalculator::default();
+    let attacker = test_stats(20, 8, 5);
+    let defender = test_stats(10, 5, 10);
+
+    // クリティカルなし（roll >= critical_rate）
+    let normal = calculator.calculate_with_roll(&attacker, &defender, &AttackType::Normal, 0.99);
+    assert_eq!(normal.damage, 15); // 20 - 10 * 0.5
+    assert!(!normal.is_critical);
+
+    let special = calculator.calculate_with_roll(&attacker, &defender, &AttackType::Special, 0.99);
+    assert_eq!(special.damage, 3); // 8 - 10 * 0.5
+}
+
+#[test]
+fn test_damage_critical_and_minimum() {
+    let calculator = DamageCalculator {
+        critical_rate: 0.5,
+        critical_multiplier: 2.0,
+        ..DamageCalculator::default()
+    };
+    let attacker = test_stats(20, 0, 0);
+    let defender = test_stats(0, 0, 10);
+
+    let critical = calculator.calculate_with_roll(&attacker, &defender, &AttackType::Normal, 0.1);
+    assert!(critical.is_critical);
+    assert_eq!(critical.damage, 30); // (20 - 5) * 2
+
+    // 攻撃力が防御を下回っても最低ダメージは保証される
+    let weak = calculator.calculate_with_roll(&attacker, &defender, &AttackType::Special, 0.9);
+    assert_eq!(weak.damage, calculator.min_damage);
+}
+
+#[test]
+fn test_apply_attack_damage_uses_server_stats() {
+    let mut game = test_game(test_stats(30, 10, 0), test_stats(10, 10, 20));
+    game.damage_calculator.critical_rate = 0.0;
+
+    // player_bがNormal攻撃を受けた → player_aの近距離攻撃力で計算
+    let result = game
+        .apply_attack_damage("player_a", "player_b", &AttackType::Normal)
+        .expect("damage should be applied");
+    assert_eq!(result.damage, 20); // 30 - 20 * 0.5
+    assert_eq!(game.characters["player_b"].hp, 80);
+
+    // 不明なプレイヤーには適用されない
+    assert!(
+        game.apply_attack_damage("player_a", "unknown", &AttackType::Normal)
+            .is_none()
+    );
+}
+
+fn attack_input(
+    player_id: &str,
+    attack_type: AttackType,
+    at: chrono::DateTime<Utc>,
+) -> PlayerInput {
+    PlayerInput {
+        player_id: player_id.to_string(),
+        action: InputAction::Attack {
+            attack_type,
+            position: Vector3::zero(),
+            direction: Vector3::new(0.0, 0.0, 1.0),
+            view_time: None,
+        },
+        seq: None,
+        timestamp: at,
+    }
+}
+
+#[test]
+fn test_attack_cooldown_per_attack_type() {
+    // attack_cooldown = 1000ms
+    let mut game = test_game(test_stats(10, 10, 0), test_stats(10, 10, 0));
+    let start = Utc::now();
+
+    assert!(
+        game.process_input(attack_input("player_a", AttackType::Normal, start))
+            .is_ok()
+    );
+
+    // クールダウン中の同種攻撃は拒否される
+    let too_fast = start + Duration::milliseconds(500);
+    assert!(
+        game.process_input(attack_input("player_a", AttackType::Normal, too_fast))
+            .is_err()
+    );
+
+    // 攻撃種別ごとに独立して管理される
+    assert!(
+        game.process_input(attack_input("player_a", AttackType::Special, too_fast))
+            .is_ok()
+    );
+
+    // プレイヤーごとにも独立
+    assert!(
+        game.process_input(attack_input("player_b", AttackType::Normal, too_fast))
+            .is_ok()
+    );
+
+    // クールダウン経過後は再び攻撃可能
+    let later = start + Duration::milliseconds(1000);
+    assert!(
+        game.process_input(attack_input("player_a", AttackType::Normal, later))
+            .is_ok()
+    );
+}
+
+#[test]
+fn test_state_update_clamps_teleport() {
+    // move_speed = 10 → 1秒あたり 10 * 1.2 + 0.5 = 12.5 まで許容
+    let mut game = test_game(test_stats(10, 10, 0), test_stats(10, 10, 0));
+    let start = game.started_at;
+
+    // 通常の移動は受理される
+    let result = game.update_state(
+        "player_a",
+        Vector3::new(5.0, 0.0, 0.0),
+        Vector3::zero(),
+        start + Duration::seconds(1),
+    );
+    assert!(matches!(result, Some(MoveValidation::Accepted)));
+    assert_eq!(game.characters["player_a"].position.x, 5.0);
+
+    // テレポートは許容距離までクランプされる
+    let result = game.update_state(
+        "player_a",
+        Vector3::new(105.0, 0.0, 0.0),
+        Vector3::zero(),
+        start + Duration::seconds(2),
+    );
+    match result {
+        Some(MoveValidation::Corrected(position)) => {
+            assert!((position.x - 17.5).abs() < 0.001);
+        }
+        other => panic!("expected correction, got {:?}", other),
+    }
+    assert!((game.characters["player_a"].position.x - 17.5).abs() < 0.001);
+
+    // 不明なプレイヤー
+    assert!(
+        game.update_state("unknown", Vector3::zero(), Vector3::zero(), start)
+            .is_none()
+    );
+}
+
+#[test]
+fn test_state_update_burst_does_not_accumulate_slack() {
+    // move_speed = 10 → 1秒間に 10 * 1.2 = 12 と持ち越し分の 0.5 まで
+    let mut game = test_game(test_stats(10, 10, 0), test_stats(10, 10, 0));
+    let start = game.started_at;
+
+    // 60Hzで毎回大きく移動しようとしても、1秒間の移動距離は許容距離を超えない
+    for i in 1..=60 {
+        let target = game.characters["player_a"].position.x + 10.0;
+        game.update_state(
+            "player_a",
+            Vector3::new(target, 0.0, 0.0),
+            Vector3::zero(),
+            start + Duration::milliseconds(i * 1000 / 60),
+        );
+    }
+    let moved = game.characters["player_a"].position.x;
+    assert!(moved <= 12.5 + 0.001, "moved {} in one second", moved);
+    assert!(moved >= 12.0 - 0.001);
+}
+
+#[test]
+fn test_state_update_rejects_non_finite_values() {
+    let mut game = test_game(test_stats(10, 10, 0), test_stats(10, 10, 0));
+    let start = game.started_at;
+    let original = game.characters["player_a"].position.clone();
+
+    for (position, rotation) in [
+        (Vector3::new(f32::NAN, 0.0, 0.0), Vector3::zero()),
+        (Vector3::new(0.0, f32::INFINITY, 0.0), Vector3::zero()),
+        (Vector3::zero(), Vector3::new(0.0, f32::NAN, 0.0)),
+    ] {
+        let result =
+            game.update_state("player_a", position, rotation, start + Duration::seconds(1));
+        match result {
+            Some(MoveValidation::Corrected(corrected)) => {
+                assert_eq!(corrected.x, original.x);
+                assert_eq!(corrected.z, original.z);
+            }
+            other => panic!("expected correction, got {:?}", other),
+        }
+        let character = &game.characters["player_a"];
+        assert!(character.position.is_finite());
+        assert!(character.rotation.is_finite());
+    }
+}
+
+#[test]
+fn test_move_input_rejects_non_finite_values() {
+    let mut game = test_game(test_stats(10, 10, 0), test_stats(10, 10, 0));
+    let now = Utc::now();
+    let move_input = |direction: Vector3, speed: f32| PlayerInput {
+        player_id: "player_a".to_string(),
+        action: InputAction::Move { direction, speed },
+        seq: None,
+        timestamp: now,
+    };
+
+    assert!(
+        game.process_input(move_input(Vector3::new(1.0, 0.0, 0.0), f32::INFINITY))
+            .is_err()
+    );
+    assert!(
+        game.process_input(move_input(Vector3::new(f32::NAN, 0.0, 0.0), 10.0))
+            .is_err()
+    );
+    assert!(game.characters["player_a"].position.is_finite());
+
+    // 以降のStateUpdateのテレポートも補正される
+    let result = game.update_state(
+        "player_a",
+        Vector3::new(500.0, 0.0, 500.0),
+        Vector3::zero(),
+        game.started_at + Duration::seconds(1),
+    );
+    assert!(matches!(result, Some(MoveValidation::Corrected(_))));
+}
+
+#[test]
+fn test_negative_move_speed_does_not_panic() {
+    let mut stats = test_stats(10, 10, 0);
+    stats.move_speed = -5;
+    let mut game = test_game(stats, test_stats(10, 10, 0));
+    let start = game.started_at;
+
+    let result = game.process_input(PlayerInput {
+        player_id: "player_a".to_string(),
+        action: InputAction::Move {
+            direction: Vector3::new(1.0, 0.0, 0.0),
+            speed: 10.0,
+        },
+        seq: None,
+        timestamp: start,
+    });
+    assert!(result.is_ok());
+    assert_eq!(game.characters["player_a"].position.x, 0.0);
+
+    // 許容距離はスラック分のみ
+    let result = game.update_state(
+        "player_a",
+        Vector3::new(3.0, 0.0, 0.0),
+        Vector3::zero(),
+        start + Duration::seconds(1),
+    );
+    assert!(matches!(result, Some(MoveValidation::Corrected(_))));
+}
+
+#[test]
+fn test_state_update_corrects_overflowing_and_non_finite_positions() {
+    let mut game = test_game(test_stats(10, 10, 0), test_stats(10, 10, 0));
+    let start = game.started_at;
+
+    // 距離の計算がオーバーフローする位置は現在の位置に補正
+    let result = game.update_state(
+        "player_a",
+        Vector3::new(f32::MAX, 0.0, -f32::MAX),
+        Vector3::zero(),
+        start + Duration::seconds(1),
+    );
+    match result {
+        Some(MoveValidation::Corrected(position)) => assert_eq!(position.x, 0.0),
+        other => panic!("expected correction, got {:?}", other),
+    }
+
+    // サーバー側の位置が有限でなくなっていても、有限の位置に戻して検証する
+    game.character_mut("player_a").unwrap().position = Vector3::new(f32::NAN, 0.0, 0.0);
+    let result = game.update_state(
+        "player_a",
+        Vector3::new(-900.0, 0.0, 900.0),
+        Vector3::zero(),
+        start + Duration::seconds(2),
+    );
+    assert!(matches!(result, Some(MoveValidation::Corrected(_))));
+    let position = &game.characters["player_a"].position;
+    assert!(position.is_finite());
+    assert!(position.x > -20.0 && position.z < 20.0);
+}
+
+#[test]
+fn test_judge_attack_uses_range_direction_and_hitbox() {
+    // attack_range = 2, player_bはMedium（ヒットボックス半径1.0）
+    let mut game = test_game(test_stats(10, 10, 0), test_stats(10, 10, 0));
+    let now = Utc::now();
+    let forward = Vector3::new(0.0, 0.0, 1.0);
+    game.character_mut("player_b").unwrap().position = Vector3::new(0.0, 0.0, 2.5);
+
+    // 正面・射程内（2 + 1.0）なら命中
+    let judgement = game
+        .judge_attack("player_a", &AttackType::Normal, &forward, None, now)
+        .unwrap();
+    assert_eq!(judgement.defender_id, "player_b");
+    assert!(judgement.hit);
+
+    // 背後を向いていれば外れ
+    let backward = Vector3::new(0.0, 0.0, -1.0);
+    let judgement = game
+        .judge_attack("player_a", &AttackType::Normal, &backward, None, now)
+        .unwrap();
+    assert!(!judgement.hit);
+
+    // 射程外なら外れ
+    game.character_mut("player_b").unwrap().position = Vector3::new(0.0, 0.0, 3.5);
+    let judgement = game
+        .judge_attack("player_a", &AttackType::Normal, &forward, None, now)
+        .unwrap();
+    assert!(!judgement.hit);
+}
+
+#[test]
+fn test_judge_attack_rewinds_defender_to_view_time() {
+    let mut game = test_game(test_stats(10, 10, 0), test_stats(10, 10, 0));
+    let start = game.started_at;
+    let forward = Vector3::new(0.0, 0.0, 1.0);
+
+    // player_bは1秒後に射程内、2秒後に射程外へ移動
+    game.update_state(
+        "player_b",
+        Vector3::new(0.0, 0.0, 2.5),
+        Vector3::zero(),
+        start + Duration::seconds(1),
+    );
+    game.update_state(
+        "player_b",
+        Vector3::new(0.0, 0.0, 10.0),
+        Vector3::zero(),
+        start + Duration::seconds(2),
+    );
+    let now = start + Duration::milliseconds(2100);
+
+    // 巻き戻しなしでは現在位置で判定され外れ
+    assert!(
+        !game
+            .judge_attack("player_a", &AttackType::Normal, &forward, None, now)
+            .unwrap()
+            .hit
+    );
+
+    // 攻撃者が見ていた時刻（射程内にいた時点）まで巻き戻せば命中
+    let view_time = start + Duration::milliseconds(1900);
+    assert_eq!(
+        game.position_at("player_b", view_time).unwrap(),
+        Vector3::new(0.0, 0.0, 2.5)
+    );
+    assert!(
+        game.judge_attack(
+            "player_a",
+            &AttackType::Normal,
+            &forward,
+            Some(view_time),
+            now
+        )
+        .unwrap()
+        .hit
+    );
+
+    // 巻き戻しは最大MAX_REWIND_MSまで（同じview_timeでも攻撃が遅れて届けば制限される）
+    let late = view_time + Duration::milliseconds(MAX_REWIND_MS + 500);
+    assert!(
+        !game
+            .judge_attack(
+                "player_a",
+                &AttackType::Normal,
+                &forward,
+                Some(view_time),
+                late
+            )
+            .unwrap()
+            .hit
+    );
+}
+
+#[test]
+fn test_projectile_hits_opponent_with_long_range_power() {
+    // long_range_attack_power = 30, 防御力10 → 30 - 10 * 0.5 = 25（クリティカル時は増加）
+    let mut game = test_game(test_stats(10, 30, 0), test_stats(10, 10, 10));
+    let start = game.started_at;
+    game.character_mut("player_b").unwrap().position = Vector3::new(0.0, 0.0, 5.0);
+
+    let projectile = game
+        .spawn_projectile("player_a", &Vector3::new(0.0, 0.0, 1.0), start)
+        .unwrap();
+    assert_eq!(projectile.owner_id, "player_a");
+    assert_eq!(game.projectiles.len(), 1);
+
+    // 100ms後は z = 2.0（ヒットボックスまで届かない）
+    assert!(
+        game.update_projectiles(start + Duration::milliseconds(100))
+            .is_empty()
+    );
+
+    // 200ms後に z = 4.0 まで進み、ヒットボックス（半径1.0 + 弾0.3）に触れる
+    let events = game.update_projectiles(start + Duration::milliseconds(200));
+    match events.as_slice() {
+        [
+            ProjectileEvent::Hit {
+                projectile: hit,
+                target_id,
+                damage,
+            },
+        ] => {
+            assert_eq!(hit.id, projectile.id);
+            assert_eq!(target_id, "player_b");
+            assert!(damage.damage >= 25);
+            assert_eq!(game.characters["player_b"].hp, 100 - damage.damage);
+        }
+        other => panic!("expected hit, got {:?}", other),
+    }
+    assert!(game.projectiles.is_empty());
+}
+
+#[test]
+fn test_projectile_expires_after_lifetime() {
+    let mut game = test_game(test_stats(10, 10, 0), test_stats(10, 10, 0));
+    let start = game.started_at;
+    game.character_mut("player_b").unwrap().position = Vector3::new(0.0, 0.0, 5.0);
+
+    // 向きが不正な弾は発射されない
+    assert!(
+        game.spawn_projectile("player_a", &Vector3::zero(), start)
+            .is_none()
+    );
+
+    // 相手と逆方向に発射した弾は寿命で消滅する
+    game.spawn_projectile("player_a", &Vector3::new(0.0, 0.0, -1.0), start)
+        .unwrap();
+    let events =
+        game.update_projectiles(start + Duration::milliseconds(PROJECTILE_LIFETIME_MS + 100));
+    match events.as_slice() {
+        [ProjectileEvent::Expired { projectile }] => {
+            // 寿命を超えては進まない
+            let max_travel = PROJECTILE_SPEED * PROJECTILE_LIFETIME_MS as f32 / 1000.0;
+            assert!((projectile.position.z + max_travel).abs() < 0.001);
+        }
+        other => panic!("expected expiry, got {:?}", other),
+    }
+    assert!(game.projectiles.is_empty());
+    assert_eq!(game.characters["player_b"].hp, 100);
+}
+
+#[test]
+fn test_damage_report_requires_confirmed_hit() {
+    let mut game = test_game(test_stats(10, 10, 0), test_stats(10, 10, 0));
+    let now = Utc::now();
+    game.character_mut("player_b").unwrap().position = Vector3::new(0.0, 0.0, 1.5);
+
+    // 命中判定がない被弾報告は一致しない
+    assert!(
+        game.take_confirmed_hit("player_b", &AttackType::Normal, now)
+            .is_none()
+    );
+    assert_eq!(game.flag_damage_report("player_b"), 1);
+
+    // 命中判定後の報告は一度だけ受理される
+    let forward = Vector3::new(0.0, 0.0, 1.0);
+    assert!(
+        game.judge_attack("player_a", &AttackType::Normal, &forward, None, now)
+            .unwrap()
+            .hit
+    );
+    assert_eq!(
+        game.take_confirmed_hit("player_b", &AttackType::Normal, now)
+            .as_deref(),
+        Some("player_a")
+    );
+    assert!(
+        game.take_confirmed_hit("player_b", &AttackType::Normal, now)
+            .is_none()
+    );
+}
+
+/// 現在のラウンドを判定して終了させ、試合終了時の結果を返す
+fn finish_round(game: &mut GameStateManager, now: chrono::DateTime<Utc>) -> GameResult {
+    let round = game.judge_round(now).expect("round should be over");
+    match game.conclude_round(&round, now) {
+        RoundProgress::MatchOver(result) => result,
+        RoundProgress::NextRound => panic!("expected match to be over"),
+    }
+}
+
+#[test]
+fn test_time_up_winner_by_hp_ratio() {
+    let game = test_game(test_stats(10, 10, 0), test_stats(10, 10, 0)).with_time_limit(60);
+    let start = game.started_at;
+
+    assert_eq!(game.remaining_seconds(start + Duration::seconds(20)), 40);
+    assert!(game.judge_round(start + Duration::seconds(59)).is_none());
+
+    let mut game = game;
+    game.character_mut("player_a").unwrap().hp = 30;
+    game.character_mut("player_b").unwrap().hp = 60;
+    let result = finish_round(&mut game, start + Duration::seconds(60));
+    assert_eq!(result.outcome, GameOutcome::Win);
+    assert_eq!(result.end_reason, EndReason::TimeUp);
+    assert_eq!(result.winner_id.as_deref(), Some("player_b"));
+    assert_eq!(result.loser_id.as_deref(), Some("player_a"));
+    assert_eq!(result.play_time_seconds, 60);
+}
+
+#[test]
+fn test_time_up_with_equal_hp_ratio_is_draw() {
+    let mut stats_b = test_stats(10, 10, 0);
+    stats_b.max_hp = 200;
+    let mut game = test_game(test_stats(10, 10, 0), stats_b).with_time_limit(30);
+    // 50/100 と 100/200 は同じ割合
+    game.character_mut("player_a").unwrap().hp = 50;
+    game.character_mut("player_b").unwrap().hp = 100;
+
+    let now = game.started_at + Duration::seconds(30);
+    let result = finish_round(&mut game, now);
+    assert_eq!(result.outcome, GameOutcome::Draw);
+    assert_eq!(result.end_reason, EndReason::TimeUp);
+    assert!(result.winner_id.is_none());
+    assert!(result.loser_id.is_none());
+}
+
+#[test]
+fn test_knock_out_records_play_time() {
+    let mut game = test_game(test_stats(10, 10, 0), test_stats(10, 10, 0));
+    game.character_mut("player_b").unwrap().hp = 0;
+
+    let now = game.started_at + Duration::seconds(42);
+    let result = finish_round(&mut game, now);
+    assert_eq!(result.end_reason, EndReason::KnockOut);
+    assert_eq!(result.winner_id.as_deref(), Some("player_a"));
+    assert_eq!(result.play_time_seconds, 42);
+}
+
+#[test]
+fn test_simultaneous_knock_out_is_draw() {
+    let mut game = test_game(test_stats(10, 10, 0), test_stats(10, 10, 0));
+    game.character_mut("player_a").unwrap().hp = 0;
+    game.character_mut("player_b").unwrap().hp = 0;
+
+    assert!(game.check_winner().is_none());
+    let now = game.started_at + Duration::seconds(10);
+    let result = finish_round(&mut game, now);
+    assert_eq!(result.outcome, GameOutcome::Draw);
+    assert_eq!(result.end_reason, EndReason::KnockOut);
+    assert!(result.winner_id.is_none());
+    assert!(result.loser_id.is_none());
+}
+
+#[test]
+fn test_best_of_three_resets_between_rounds() {
+    let mut game = test_game(test_stats(10, 10, 0), test_stats(10, 10, 0)).with_rounds(3);
+    assert_eq!(game.wins_required(), 2);
+    let start = game.started_at;
+
+    // 1ラウンド目: player_aの勝利
+    game.character_mut("player_b").unwrap().hp = 0;
+    game.character_mut("player_a").unwrap().hp = 40;
+    game.character_mut("player_a").unwrap().position = Vector3::new(3.0, 0.0, 0.0);
+    let now = start + Duration::seconds(20);
+    let round = game.judge_round(now).unwrap();
+    assert_eq!(round.round, 1);
+    assert!(matches!(
+        game.conclude_round(&round, now),
+        RoundProgress::NextRound
+    ));
+
+    // HP・位置がリセットされ、ラウンド時間も再スタート
+    assert_eq!(game.current_round, 2);
+    assert_eq!(game.scores["player_a"], 1);
+    assert_eq!(game.characters["player_a"].hp, 100);
+    assert_eq!(game.characters["player_b"].hp, 100);
+    assert_eq!(game.characters["player_a"].position.x, 0.0);
+    assert_eq!(game.remaining_seconds(now), game.time_limit_seconds);
+
+    // 2ラウンド目: 同時KOは引き分け（スコアは変わらない）
+    game.character_mut("player_a").unwrap().hp = 0;
+    game.character_mut("player_b").unwrap().hp = 0;
+    let now = start + Duration::seconds(40);
+    let round = game.judge_round(now).unwrap();
+    assert!(round.winner_id.is_none());
+    assert!(matches!(
+        game.conclude_round(&round, now),
+        RoundProgress::NextRound
+    ));
+
+    // 3ラウンド目: player_aが過半数に到達して試合終了
+    game.character_mut("player_b").unwrap().hp = 0;
+    let result = finish_round(&mut game, start + Duration::seconds(60));
+    assert_eq!(result.winner_id.as_deref(), Some("player_a"));
+    assert_eq!(result.scores["player_a"], 2);
+    assert_eq!(result.scores["player_b"], 0);
+    assert_eq!(result.play_time_seconds, 60);
+}
+
+#[test]
+fn test_match_ends_once_majority_is_reached() {
+    let mut game = test_game(test_stats(10, 10, 0), test_stats(10, 10, 0)).with_rounds(5);
+    let start = game.started_at;
+
+    for round in 1..=3 {
+        game.character_mut("player_a").unwrap().hp = 0;
+        let now = start + Duration::seconds(round * 10);
+        let result = game.judge_round(now).unwrap();
+        match game.conclude_round(&result, now) {
+            RoundProgress::NextRound => assert!(round < 3),
+            RoundProgress::MatchOver(result) => {
+                assert_eq!(round, 3);
+                assert_eq!(result.winner_id.as_deref(), Some("player_b"));
+                assert_eq!(result.scores["player_b"], 3);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_surrender_awards_opponent() {
+    let mut game = test_game(test_stats(10, 10, 0), test_stats(10, 10, 0)).with_rounds(3);
+    game.scores.insert("player_a".to_string(), 1);
+
+    let now = game.started_at + Duration::seconds(15);
+    let result = game.surrender("player_a", now).unwrap();
+    assert_eq!(result.outcome, GameOutcome::Win);
+    assert_eq!(result.end_reason, EndReason::Surrender);
+    assert_eq!(result.winner_id.as_deref(), Some("player_b"));
+    assert_eq!(result.loser_id.as_deref(), Some("player_a"));
+    assert_eq!(result.play_time_seconds, 15);
+
+    assert!(game.surrender("unknown", now).is_none());
+}
+
+#[test]
+fn test_disconnect_pauses_and_resume_excludes_paused_time() {
+    let mut game = test_game(test_stats(10, 10, 0), test_stats(10, 10, 0)).with_time_limit(60);
+    let start = game.started_at;
+
+    let disconnected_at = start + Duration::seconds(20);
+    let deadline = game
+        .pause_for_disconnect("player_a", disconnected_at)
+        .unwrap();
+    assert_eq!(
+        deadline,
+        disconnected_at + Duration::seconds(DISCONNECT_GRACE_SECONDS)
+    );
+    assert!(game.is_paused());
+
+    // 一時停止中は残り時間が減らない
+    let during_pause = disconnected_at + Duration::seconds(10);
+    assert_eq!(game.remaining_seconds(during_pause), 40);
+    assert!(game.judge_disconnect(during_pause).is_none());
+
+    // 再接続すると停止していた時間を除いて再開
+    assert!(game.resume_player("player_a", during_pause));
+    assert!(!game.is_paused());
+    assert_eq!(game.remaining_seconds(during_pause), 40);
+    assert_eq!(game.elapsed_seconds(during_pause), 20);
+    // 実際の開始時刻は一時停止しても変わらない
+    assert_eq!(game.actual_started_at, start);
+
+    // 切断していないプレイヤーの再接続は無視
+    assert!(!game.resume_player("player_b", during_pause));
+}
+
+#[test]
+fn test_disconnect_forfeit_after_grace_period() {
+    let mut game = test_game(test_stats(10, 10, 0), test_stats(10, 10, 0));
+    let now = game.started_at + Duration::seconds(5);
+    let deadline = game.pause_for_disconnect("player_b", now).unwrap();
+
+    let result = game.judge_disconnect(deadline).unwrap();
+    assert_eq!(result.end_reason, EndReason::Disconnect);
+    assert_eq!(result.winner_id.as_deref(), Some("player_a"));
+    assert_eq!(result.loser_id.as_deref(), Some("player_b"));
+
+    // 両者とも期限切れの場合は引き分け
+    game.pause_for_disconnect("player_a", now).unwrap();
+    let result = game.judge_disconnect(deadline).unwrap();
+    assert_eq!(result.outcome, GameOutcome::Draw);
+    assert_eq!(result.end_reason, EndReason::Disconnect);
+}
+
+fn three_player_game() -> GameStateManager {
+    let players = ["player_a", "player_b", "player_c"]
+        .into_iter()
+        .map(|id| {
+            let character = Character::new(format!("model_{}", id), test_stats(10, 10, 0));
+            (id.to_string(), character)
+        })
+        .collect();
+    GameStateManager::new(Uuid::new_v4(), players)
+}
+
+#[test]
+fn test_judge_attack_targets_nearest_opponent() {
+    let mut game = three_player_game();
+    let now = Utc::now();
+    game.character_mut("player_b").unwrap().position = Vector3::new(0.0, 0.0, 2.5);
+    game.character_mut("player_c").unwrap().position = Vector3::new(0.0, 0.0, 1.5);
+
+    // 射程内に2人いる場合は近い方に命中
+    let forward = Vector3::new(0.0, 0.0, 1.0);
+    let judgement = game
+        .judge_attack("player_a", &AttackType::Normal, &forward, None, now)
+        .unwrap();
+    assert!(judgement.hit);
+    assert_eq!(judgement.defender_id, "player_c");
+    assert_eq!(
+        game.take_confirmed_hit("player_c", &AttackType::Normal, now)
+            .as_deref(),
+        Some("player_a")
+    );
+    assert!(
+        game.take_confirmed_hit("player_b", &AttackType::Normal, now)
+            .is_none()
+    );
+}
+
+#[test]
+fn test_confirmed_hits_from_multiple_attackers_are_kept() {
+    let mut game = three_player_game();
+    let now = Utc::now();
+    game.character_mut("player_b").unwrap().position = Vector3::new(0.0, 0.0, 2.5);
+    game.character_mut("player_c").unwrap().position = Vector3::new(0.0, 0.0, 5.0);
+
+    // player_aとplayer_cが続けてplayer_bに命中させる
+    for (attacker_id, direction) in [("player_a", 1.0), ("player_c", -1.0)] {
+        let judgement = game
+            .judge_attack(
+                attacker_id,
+                &AttackType::Normal,
+                &Vector3::new(0.0, 0.0, direction),
+                None,
+                now,
+            )
+            .unwrap();
+        assert!(judgement.hit);
+        assert_eq!(judgement.defender_id, "player_b");
+    }
+
+    // 被弾報告ごとに命中した順で1件ずつ消費される
+    let attackers: Vec<Option<String>> = (0..3)
+        .map(|_| game.take_confirmed_hit("player_b", &AttackType::Normal, now))
+        .collect();
+    assert_eq!(
+        attackers,
+        [
+            Some("player_a".to_string()),
+            Some("player_c".to_string()),
+            None
+        ]
+    );
+}
+
+#[test]
+fn test_last_one_standing_wins_with_placements() {
+    let mut game = three_player_game();
+    let now = game.started_at + Duration::seconds(20);
+
+    // 1人脱落しても2人残っていればラウンドは続く
+    assert_eq!(game.apply_damage("player_b", 100), Some(0));
+    assert!(game.check_winner().is_none());
+    assert!(game.judge_round(now).is_none());
+
+    game.apply_damage("player_a", 100);
+    assert_eq!(game.check_winner().as_deref(), Some("player_c"));
+
+    // HP0になるのが遅いほど上位
+    let result = finish_round(&mut game, now);
+    assert_eq!(result.end_reason, EndReason::KnockOut);
+    assert_eq!(result.winner_id.as_deref(), Some("player_c"));
+    assert_eq!(result.loser_id.as_deref(), Some("player_b"));
+    assert_eq!(result.player_ids, ["player_a", "player_b", "player_c"]);
+    assert_eq!(result.placements, ["player_c", "player_a", "player_b"]);
+}
+
+#[test]
+fn test_eliminated_and_forfeited_players_cannot_act() {
+    let mut game = three_player_game();
+    let now = game.started_at + Duration::seconds(10);
+    assert!(game.can_act("player_a"));
+
+    game.character_mut("player_a").unwrap().hp = 0;
+    assert!(!game.can_act("player_a"));
+
+    assert!(game.surrender("player_b", now).is_none());
+    assert!(!game.can_act("player_b"));
+    assert!(game.can_act("player_c"));
+    assert!(!game.can_act("unknown"));
+}
+
+#[test]
+fn test_surrender_in_three_player_match_continues() {
+    let mut game = three_player_game();
+    let now = game.started_at + Duration::seconds(10);
+
+    // 残りが2人以上なら降参したプレイヤーのみ脱落
+    assert!(game.surrender("player_b", now).is_none());
+    assert_eq!(game.forfeited, ["player_b"]);
+    assert_eq!(game.characters["player_b"].hp, 0);
+    assert_eq!(game.active_players(), ["player_a", "player_c"]);
+    assert!(game.surrender("player_b", now).is_none());
+
+    // 残りが1人になったら試合終了
+    let result = game.surrender("player_a", now).unwrap();
+    assert_eq!(result.end_reason, EndReason::Surrender);
+    assert_eq!(result.winner_id.as_deref(), Some("player_c"));
+    assert_eq!(result.placements, ["player_c", "player_a", "player_b"]);
+}
+
+/// player_a・player_bがチームA、player_c・player_dがチームBの2対2
+fn team_game(friendly_fire: bool) -> GameStateManager {
+    let players = ["player_a", "player_b", "player_c", "player_d"];
+    let teams = players
+        .iter()
+        .zip([Team::A, Team::A, Team::B, Team::B])
+        .map(|(id, team)| (id.to_string(), team))
+        .collect();
+    let players = players
+        .into_iter()
+        .map(|id| {
+            let character = Character::new(format!("model_{}", id), test_stats(10, 10, 0));
+            (id.to_string(), character)
+        })
+        .collect();
+    GameStateManager::new(Uuid::new_v4(), players).with_teams(teams, friendly_fire)
+}
+
+#[test]
+fn test_team_attacks_skip_teammates_without_friendly_fire() {
+    let mut game = team_game(false);
+    let now = Utc::now();
+    game.character_mut("player_b").unwrap().position = Vector3::new(0.0, 0.0, 1.5);
+    game.character_mut("player_c").unwrap().position = Vector3::new(0.0, 0.0, 2.5);
+    game.character_mut("player_d").unwrap().position = Vector3::new(0.0, 0.0, -10.0);
+
+    // 手前の味方は素通りして相手チームに命中
+    let forward = Vector3::new(0.0, 0.0, 1.0);
+    let judgement = game
+        .judge_attack("player_a", &AttackType::Normal, &forward, None, now)
+        .unwrap();
+    assert_eq!(judgement.defender_id, "player_c");
+    assert!(
+        game.apply_attack_damage("player_a", "player_b", &AttackType::Normal)
+            .is_none()
+    );
+    assert_eq!(game.characters["player_b"].hp, 100);
+
+    // フレンドリーファイア有効時は味方にも当たる
+    let mut game = team_game(true);
+    game.character_mut("player_b").unwrap().position = Vector3::new(0.0, 0.0, 1.5);
+    game.character_mut("player_c").unwrap().position = Vector3::new(0.0, 0.0, 2.5);
+    game.character_mut("player_d").unwrap().position = Vector3::new(0.0, 0.0, -10.0);
+    let judgement = game
+        .judge_attack("player_a", &AttackType::Normal, &forward, None, now)
+        .unwrap();
+    assert_eq!(judgement.defender_id, "player_b");
+}
+
+#[test]
+fn test_last_team_standing_wins() {
+    let mut game = team_game(false);
+    let now = game.started_at + Duration::seconds(30);
+
+    game.apply_damage("player_c", 100);
+    assert!(game.judge_round(now).is_none());
+    game.apply_damage("player_a", 100);
+    game.apply_damage("player_d", 100);
+    assert_eq!(game.check_winning_team(), Some(Team::A));
+
+    // 倒されたplayer_aも勝利チームとして上位に入る
+    let result = finish_round(&mut game, now);
+    assert_eq!(result.outcome, GameOutcome::Win);
+    assert_eq!(result.winning_team, Some(Team::A));
+    assert!(result.winner_id.is_none());
+    assert_eq!(result.scores["player_a"], 1);
+    assert_eq!(result.scores["player_c"], 0);
+    assert_eq!(
+        result.placements,
+        ["player_b", "player_a", "player_d", "player_c"]
+    );
+    assert_eq!(result.loser_id.as_deref(), Some("player_c"));
+}
+
+#[test]
+fn test_team_surrender_ends_when_one_team_remains() {
+    let mut game = team_game(false);
+    let now = game.started_at + Duration::seconds(10);
+
+    assert!(game.surrender("player_c", now).is_none());
+    let result = game.surrender("player_d", now).unwrap();
+    assert_eq!(result.end_reason, EndReason::Surrender);
+    assert_eq!(result.winning_team, Some(Team::A));
+}
+
+#[test]
+fn test_snapshot_delta_against_acknowledged_tick() {
+    let mut tracker = SnapshotTracker::new(2);
+    assert!(!tracker.is_due(1));
+    assert!(tracker.is_due(2));
+
+    let mut a = Character::new("model_a".to_string(), test_stats(10, 10, 0));
+    let b = Character::new("model_b".to_string(), test_stats(10, 10, 0));
+
+    // ACK前は全量
+    let (baseline, deltas) = tracker.delta_for("player_a", &[("player_a", &a), ("player_b", &b)]);
+    assert!(baseline.is_none());
+    assert_eq!(deltas.len(), 2);
+    assert!(
+        deltas
+            .iter()
+            .all(|d| d.position.is_some() && d.hp.is_some())
+    );
+    tracker.record(2, &[("player_a", &a), ("player_b", &b)]);
+
+    // 未送信のティックや古いACKは無視
+    assert!(!tracker.acknowledge("player_a", 3));
+    assert!(tracker.acknowledge("player_a", 2));
+    assert!(!tracker.acknowledge("player_a", 2));
+
+    // ACK済みティックから変化したフィールドのみ
+    a.position = Vector3::new(1.0, 0.0, 0.0);
+    let (baseline, deltas) = tracker.delta_for("player_a", &[("player_a", &a), ("player_b", &b)]);
+    assert_eq!(baseline, Some(2));
+    assert_eq!(deltas.len(), 1);
+    assert_eq!(deltas[0].player_id, "player_a");
+    assert!(deltas[0].position.is_some());
+    assert!(deltas[0].rotation.is_none());
+    assert!(deltas[0].hp.is_none());
+
+    // ACKしていないプレイヤーには全量
+    let (baseline, deltas) = tracker.delta_for("player_b", &[("player_a", &a), ("player_b", &b)]);
+    assert!(baseline.is_none());
+    assert_eq!(deltas.len(), 2);
+}
+
+#[test]
+fn test_sequence_numbers_drop_duplicates_and_out_of_order() {
+    let mut game = test_game(test_stats(10, 10, 0), test_stats(10, 10, 0));
+
+    // シーケンス番号なしは常に受理
+    assert!(game.accept_sequence("player_a", None));
+    assert_eq!(game.last_processed_seq("player_a"), None);
+
+    assert!(game.accept_sequence("player_a", Some(1)));
+    assert!(game.accept_sequence("player_a", Some(3)));
+    // 重複・順序逆転は破棄
+    assert!(!game.accept_sequence("player_a", Some(3)));
+    assert!(!game.accept_sequence("player_a", Some(2)));
+    assert_eq!(game.last_processed_seq("player_a"), Some(3));
+
+    // プレイヤーごとに独立
+    assert!(game.accept_sequence("player_b", Some(1)));
+    assert_eq!(game.last_processed_seq("player_b"), Some(1));
+}
@@ -0,0 +1,76 @@
+use webscoket_realtime_prac::geometry::{
+    Aabb, Capsule, Ray, Shape, closest_point_on_segment, segment_distance,
+};
+use webscoket_realtime_prac::models::Vector3;
+
+fn unit_box() -> Aabb {
+    Aabb {
+        min: Vector3::new(-1.0, 0.0, -1.0),
+        max: Vector3::new(1.0, 2.0, 1.0),
+    }
+}
+
+#[test]
+fn test_ray_aabb_intersection() {
+    let aabb = unit_box();
+
+    // 箱を貫通するレイは手前の面までの距離を返す
+    let ray = Ray::between(&Vector3::new(0.0, 1.0, -5.0), &Vector3::new(0.0, 1.0, 5.0));
+    let hit = aabb.raycast(&ray).unwrap();
+    assert!((hit - 4.0).abs() < 0.001);
+
+    // 箱の手前で終わるレイは交差しない
+    let short = Ray::between(&Vector3::new(0.0, 1.0, -5.0), &Vector3::new(0.0, 1.0, -2.0));
+    assert!(aabb.raycast(&short).is_none());
+
+    // 箱の上を通るレイは交差しない
+    let above = Ray::between(&Vector3::new(0.0, 3.0, -5.0), &Vector3::new(0.0, 3.0, 5.0));
+    assert!(aabb.raycast(&above).is_none());
+
+    // 始点が内部にある場合は距離0
+    let inside = Ray::between(&Vector3::new(0.0, 1.0, 0.0), &Vector3::new(0.0, 1.0, 5.0));
+    assert_eq!(aabb.raycast(&inside), Some(0.0));
+}
+
+#[test]
+fn test_ray_capsule_intersection() {
+    // 原点に立つ高さ4・半径1の柱
+    let pillar = Shape::Capsule(Capsule {
+        start: Vector3::zero(),
+        end: Vector3::new(0.0, 4.0, 0.0),
+        radius: 1.0,
+    });
+
+    let through = Ray::between(&Vector3::new(-5.0, 1.0, 0.5), &Vector3::new(5.0, 1.0, 0.5));
+    assert!(pillar.intersects(&through));
+
+    let beside = Ray::between(&Vector3::new(-5.0, 1.0, 1.5), &Vector3::new(5.0, 1.0, 1.5));
+    assert!(!pillar.intersects(&beside));
+
+    assert!(Shape::Aabb(unit_box()).intersects(&through));
+}
+
+#[test]
+fn test_segment_helpers() {
+    let start = Vector3::zero();
+    let end = Vector3::new(0.0, 0.0, 10.0);
+
+    // 線分上の最近点は両端でクランプされる
+    assert_eq!(
+        closest_point_on_segment(&start, &end, &Vector3::new(3.0, 0.0, 4.0)),
+        Vector3::new(0.0, 0.0, 4.0)
+    );
+    assert_eq!(
+        closest_point_on_segment(&start, &end, &Vector3::new(0.0, 0.0, 20.0)),
+        end
+    );
+
+    // ねじれの位置にある線分の距離
+    let distance = segment_distance(
+        &start,
+        &end,
+        &Vector3::new(-5.0, 2.0, 5.0),
+        &Vector3::new(5.0, 2.0, 5.0),
+    );
+    assert!((distance - 2.0).abs() < 0.001);
+}
@@ -0,0 +1,199 @@
+use chrono::Utc;
+use sqlx::SqlitePool;
+use sqlx::sqlite::SqlitePoolOptions;
+use std::collections::HashMap;
+use uuid::Uuid;
+use webscoket_realtime_prac::db::models::MatchRecord;
+use webscoket_realtime_prac::models::{EndReason, GameOutcome, GameResult, Team};
+
+async fn test_pool() -> SqlitePool {
+    let db_path = std::env::temp_dir().join(format!("test_matches_{}.db", Uuid::new_v4()));
+    let db_url = format!("sqlite:{}?mode=rwc", db_path.to_str().unwrap());
+    let pool = SqlitePoolOptions::new()
+        .max_connections(5)
+        .connect(&db_url)
+        .await
+        .expect("Failed to create test database pool");
+    sqlx::migrate!("./migrations")
+        .run(&pool)
+        .await
+        .expect("Failed to run migrations");
+    pool
+}
+
+fn test_result(player_ids: &[&str], winner_id: Option<&str>) -> GameResult {
+    let player_ids: Vec<String> = player_ids.iter().map(|id| id.to_string()).collect();
+    let mut placements = player_ids.clone();
+    if let Some(winner_id) = winner_id {
+        placements.retain(|id| id != winner_id);
+        placements.insert(0, winner_id.to_string());
+    }
+    GameResult {
+        matching_id: Uuid::new_v4(),
+        game_id: Uuid::new_v4(),
+        outcome: GameOutcome::Win,
+        winner_id: winner_id.map(|id| id.to_string()),
+        winning_team: None,
+        loser_id: placements.last().cloned(),
+        scores: player_ids
+            .iter()
+            .map(|id| (id.clone(), u32::from(Some(id.as_str()) == winner_id)))
+            .collect(),
+        player_ids,
+        placements,
+        end_reason: EndReason::KnockOut,
+        play_time_seconds: 95,
+        finished_at: Utc::now(),
+    }
+}
+
+fn monster_ids(player_ids: &[&str]) -> HashMap<String, String> {
+    player_ids
+        .iter()
+        .map(|id| (id.to_string(), format!("monster_{}", id)))
+        .collect()
+}
+
+#[test]
+fn test_from_result_records_players_in_join_order() {
+    let players = ["player_a", "player_b", "player_c"];
+    let result = test_result(&players, Some("player_c"));
+    let started_at = Utc::now() - chrono::Duration::seconds(95);
+
+    let record = MatchRecord::from_result(
+        &result,
+        &monster_ids(&players),
+        &HashMap::new(),
+        3,
+        started_at,
+    );
+
+    assert_eq!(record.id, result.game_id.to_string());
+    assert_eq!(record.matching_id, result.matching_id.to_string());
+    assert_eq!(record.outcome, "Win");
+    assert_eq!(record.winner_id, Some("player_c".to_string()));
+    assert_eq!(record.winning_team, None);
+    assert_eq!(record.end_reason, "KnockOut");
+    assert_eq!(record.rounds, 3);
+    assert_eq!(record.play_time_seconds, 95);
+    assert_eq!(record.started_at, started_at.to_rfc3339());
+
+    let summary: Vec<(&str, &str, i64, i64)> = record
+        .players
+        .iter()
+        .map(|player| {
+            (
+                player.player_id.as_str(),
+                player.monster_id.as_str(),
+                player.placement,
+                player.score,
+            )
+        })
+        .collect();
+    assert_eq!(
+        summary,
+        vec![
+            ("player_a", "monster_player_a", 2, 0),
+            ("player_b", "monster_player_b", 3, 0),
+            ("player_c", "monster_player_c", 1, 1),
+        ]
+    );
+}
+
+#[test]
+fn test_from_result_records_teams() {
+    let players = ["player_a", "player_b", "player_c", "player_d"];
+    let mut result = test_result(&players, None);
+    result.winning_team = Some(Team::B);
+    let teams = HashMap::from([
+        ("player_a".to_string(), Team::A),
+        ("player_b".to_string(), Team::B),
+        ("player_c".to_string(), Team::A),
+        ("player_d".to_string(), Team::B),
+    ]);
+
+    let record = MatchRecord::from_result(&result, &monster_ids(&players), &teams, 1, Utc::now());
+
+    assert_eq!(record.winner_id, None);
+    assert_eq!(record.winning_team, Some("B".to_string()));
+    let player_teams: Vec<Option<&str>> = record
+        .players
+        .iter()
+        .map(|player| player.team.as_deref())
+        .collect();
+    assert_eq!(
+        player_teams,
+        vec![Some("A"), Some("B"), Some("A"), Some("B")]
+    );
+}
+
+#[actix_rt::test]
+async fn test_insert_and_query_match_records() {
+    let pool = test_pool().await;
+
+    let first = MatchRecord::from_result(
+        &test_result(&["player_a", "player_b"], Some("player_a")),
+        &monster_ids(&["player_a", "player_b"]),
+        &HashMap::new(),
+        1,
+        Utc::now(),
+    );
+    first.insert(&pool).await.unwrap();
+
+    let mut second_result = test_result(&["player_b", "player_c"], Some("player_c"));
+    second_result.finished_at = Utc::now() + chrono::Duration::seconds(60);
+    let second = MatchRecord::from_result(
+        &second_result,
+        &monster_ids(&["player_b", "player_c"]),
+        &HashMap::new(),
+        1,
+        Utc::now(),
+    );
+    second.insert(&pool).await.unwrap();
+
+    // IDで取得すると参加者も参加順で取得できる
+    let found = MatchRecord::find_by_id(&pool, &first.id)
+        .await
+        .unwrap()
+        .expect("inserted match should be found");
+    assert_eq!(found.matching_id, first.matching_id);
+    assert_eq!(found.winner_id, Some("player_a".to_string()));
+    assert_eq!(found.end_reason, "KnockOut");
+    let found_players: Vec<(&str, &str)> = found
+        .players
+        .iter()
+        .map(|player| (player.player_id.as_str(), player.monster_id.as_str()))
+        .collect();
+    assert_eq!(
+        found_players,
+        vec![
+            ("player_a", "monster_player_a"),
+            ("player_b", "monster_player_b")
+        ]
+    );
+    assert!(
+        MatchRecord::find_by_id(&pool, "missing")
+            .await
+            .unwrap()
+            .is_none()
+    );
+
+    // プレイヤーごとの試合結果は新しい順
+    let history = MatchRecord::list_by_player(&pool, "player_b", 10)
+        .await
+        .unwrap();
+    let history_ids: Vec<&str> = history.iter().map(|record| record.id.as_str()).collect();
+    assert_eq!(history_ids, vec![second.id.as_str(), first.id.as_str()]);
+    assert_eq!(history[0].players.len(), 2);
+
+    let limited = MatchRecord::list_by_player(&pool, "player_b", 1)
+        .await
+        .unwrap();
+    assert_eq!(limited.len(), 1);
+    assert!(
+        MatchRecord::list_by_player(&pool, "player_z", 10)
+            .await
+            .unwrap()
+            .is_empty()
+    );
+}
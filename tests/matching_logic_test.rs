@@ -7,7 +7,10 @@ use tokio::time::sleep;
 use uuid::Uuid;
 use webscoket_realtime_prac::game::manager::GameManager;
 use webscoket_realtime_prac::handlers::MatchingSessions;
-use webscoket_realtime_prac::models::{MatchingSession, MatchingStatus, Player};
+use webscoket_realtime_prac::models::{
+    Character, MatchSettings, MatchingInfo, MatchingSession, MatchingStatus, MonsterStats, Player,
+    REMATCH_WINDOW_SECONDS, Team,
+};
 
 #[actix_rt::test]
 async fn test_matching_validity_logic() {
@@ -22,13 +25,18 @@ async fn test_matching_validity_logic() {
     let mut session = MatchingSession {
         matching_id,
         creator_username: None,
-        player_a: Player::new(player_a_id.clone()),
-        player_b: Some(Player::new(player_b_id.clone())),
+        players: vec![
+            Player::new(player_a_id.clone()),
+            Player::new(player_b_id.clone()),
+        ],
         status: MatchingStatus::Matched,
         created_at: Utc::now(),
         last_active_at: None,
         is_battle_started: false,
         is_battle_finished: false,
+        settings: MatchSettings::default(),
+        rematch_deadline: None,
+        rematch_requested_by: None,
     };
 
     // 2. Verify valid initially
@@ -52,6 +60,59 @@ async fn test_matching_validity_logic() {
     session.last_active_at = None;
     session.is_battle_finished = true;
     assert!(!session.is_valid());
+
+    // 7. Verify valid while the rematch window is open, invalid after it closes
+    session.rematch_deadline = Some(Utc::now() + Duration::seconds(REMATCH_WINDOW_SECONDS));
+    assert!(session.is_valid());
+    session.rematch_deadline = Some(Utc::now() - Duration::seconds(1));
+    assert!(!session.is_valid());
+}
+
+#[test]
+fn test_reset_for_rematch() {
+    let mut session = MatchingSession::new_with_username("player_a".to_string(), None);
+    let mut player_b = Player::new("player_b".to_string());
+    player_b.ready = true;
+    player_b.character = Some(Character::new(
+        "model_b".to_string(),
+        MonsterStats {
+            name: "Test Monster".to_string(),
+            max_hp: 100,
+            short_range_attack_power: 10,
+            long_range_attack_power: 10,
+            defense_power: 5,
+            move_speed: 10,
+            attack_range: 2,
+            attack_cooldown: 1000,
+            size_type: "Medium".to_string(),
+        },
+    ));
+    session.players.push(player_b);
+    session.players[0].ready = true;
+    session.status = MatchingStatus::Finished;
+    session.is_battle_started = true;
+    session.is_battle_finished = true;
+    session.rematch_deadline = Some(Utc::now() + Duration::seconds(REMATCH_WINDOW_SECONDS));
+    session.rematch_requested_by = Some("player_a".to_string());
+
+    assert!(session.is_rematch_open());
+    assert!(session.is_participant("player_b"));
+    assert!(!session.is_participant("player_c"));
+
+    session.reset_for_rematch();
+
+    // 同じマッチングのままキャラクター選択からやり直す
+    let player_b = session.player("player_b").unwrap();
+    assert_eq!(session.status, MatchingStatus::Matched);
+    assert!(!session.is_battle_started);
+    assert!(!session.is_battle_finished);
+    assert!(!session.is_rematch_open());
+    assert!(session.rematch_requested_by.is_none());
+    assert!(!session.player("player_a").unwrap().ready);
+    assert!(!player_b.ready);
+    assert!(player_b.character.is_none());
+    assert_eq!(player_b.previous_model_id.as_deref(), Some("model_b"));
+    assert!(session.is_valid());
 }
 
 #[actix_rt::test]
@@ -66,13 +127,15 @@ async fn test_cleanup_task() {
     let session = MatchingSession {
         matching_id,
         creator_username: None,
-        player_a: Player::new("a".to_string()),
-        player_b: Some(Player::new("b".to_string())),
+        players: vec![Player::new("a".to_string()), Player::new("b".to_string())],
         status: MatchingStatus::Matched,
         created_at: Utc::now(),
         last_active_at: Some(Utc::now() - Duration::seconds(65)), // Expired
         is_battle_started: false,
         is_battle_finished: false,
+        settings: MatchSettings::default(),
+        rematch_deadline: None,
+        rematch_requested_by: None,
     };
 
     matching_sessions
@@ -92,3 +155,127 @@ async fn test_cleanup_task() {
             .is_none()
     );
 }
+
+#[test]
+fn test_matching_fills_up_to_max_players() {
+    let mut session = MatchingSession::new_with_username("player_a".to_string(), None);
+    session.settings.max_players = 3;
+
+    session.players.push(Player::new("player_b".to_string()));
+    assert!(!session.is_full());
+    session.players.push(Player::new("player_c".to_string()));
+    assert!(session.is_full());
+
+    assert_eq!(session.creator_id(), "player_a");
+    assert_eq!(
+        session.other_player_ids("player_b"),
+        vec!["player_a".to_string(), "player_c".to_string()]
+    );
+
+    // 全員が準備完了になるまでゲームは開始しない
+    for player_id in ["player_a", "player_b"] {
+        session.player_mut(player_id).unwrap().ready = true;
+    }
+    assert!(!session.is_all_ready());
+    session.player_mut("player_c").unwrap().ready = true;
+    assert!(session.is_all_ready());
+}
+
+#[test]
+fn test_max_players_validation() {
+    let mut settings = MatchSettings::default();
+    assert_eq!(settings.max_players, 2);
+
+    settings.max_players = 4;
+    assert!(settings.validate().is_ok());
+    settings.max_players = 1;
+    assert!(settings.validate().is_err());
+    settings.max_players = 9;
+    assert!(settings.validate().is_err());
+
+    // ボット対戦は2人のみ
+    settings.max_players = 3;
+    settings.bot_difficulty = Some(Default::default());
+    assert!(settings.validate().is_err());
+}
+
+#[test]
+fn test_team_assignment_balances_and_rejects_full_team() {
+    let mut session = MatchingSession::new_with_username("player_a".to_string(), None);
+    session.settings.team_mode = true;
+    session.settings.max_players = 4;
+    session.players[0].team = Some(Team::A);
+
+    // 希望がなければ人数の少ないチーム
+    let join = |session: &mut MatchingSession, id: &str, requested: Option<Team>| {
+        let team = session.assign_team(requested)?;
+        let mut player = Player::new(id.to_string());
+        player.team = team;
+        session.players.push(player);
+        Ok::<_, String>(team)
+    };
+    assert_eq!(join(&mut session, "player_b", None), Ok(Some(Team::B)));
+    assert_eq!(
+        join(&mut session, "player_c", Some(Team::A)),
+        Ok(Some(Team::A))
+    );
+    assert!(join(&mut session, "player_d", Some(Team::A)).is_err());
+
+    // ロビー向けにチームごとの空き枠を表示
+    let info = MatchingInfo::from_session(&session);
+    let teams = info.teams.unwrap();
+    assert_eq!(teams[0].player_ids, ["player_a", "player_c"]);
+    assert_eq!(teams[0].open_slots, 0);
+    assert_eq!(teams[1].player_ids, ["player_b"]);
+    assert_eq!(teams[1].open_slots, 1);
+
+    assert_eq!(join(&mut session, "player_d", None), Ok(Some(Team::B)));
+    assert!(session.is_full());
+    assert_eq!(session.teams()["player_d"], Team::B);
+}
+
+#[test]
+fn test_player_leaving_before_matched_frees_slot_and_team() {
+    let mut session = MatchingSession::new_with_username("player_a".to_string(), None);
+    session.settings.team_mode = true;
+    session.settings.max_players = 4;
+    session.players[0].team = Some(Team::A);
+    let mut player = Player::new("player_b".to_string());
+    player.team = Some(Team::B);
+    session.players.push(player);
+
+    // 作成者は外さない
+    assert!(!session.remove_waiting_player("player_a"));
+    assert!(!session.remove_waiting_player("player_c"));
+
+    assert!(session.remove_waiting_player("player_b"));
+    assert!(!session.is_participant("player_b"));
+    assert!(session.team_members(Team::B).is_empty());
+
+    // 成立後は外さない
+    let mut player = Player::new("player_c".to_string());
+    player.team = Some(Team::B);
+    session.players.push(player);
+    session.status = MatchingStatus::Matched;
+    assert!(!session.remove_waiting_player("player_c"));
+    assert!(session.is_participant("player_c"));
+}
+
+#[test]
+fn test_team_mode_validation() {
+    let mut settings = MatchSettings {
+        team_mode: true,
+        max_players: 4,
+        ..MatchSettings::default()
+    };
+    assert!(settings.validate().is_ok());
+    settings.max_players = 3;
+    assert!(settings.validate().is_err());
+    settings.max_players = 2;
+    assert!(settings.validate().is_err());
+
+    // 個人戦ではチーム情報を持たない
+    let session = MatchingSession::new_with_username("player_a".to_string(), None);
+    assert!(MatchingInfo::from_session(&session).teams.is_none());
+    assert!(session.assign_team(Some(Team::B)).unwrap().is_none());
+}
@@ -10,9 +10,12 @@ use tokio::time::{Duration, timeout};
 use tokio_tungstenite::{connect_async, tungstenite::Message};
 use uuid::Uuid;
 use webscoket_realtime_prac::db::models::Model3D;
+use webscoket_realtime_prac::game::arena::{ARENA_DATA_DIR, ArenaRegistry};
 use webscoket_realtime_prac::game::manager::GameManager;
+use webscoket_realtime_prac::game::replay::ReplayStore;
 use webscoket_realtime_prac::handlers::{
-    LobbyPlayers, MatchingSessions, WaitingPlayers, WsChannels, list_models, ws_handler,
+    AppState, Arenas, LobbyPlayers, MatchingSessions, Replays, WaitingPlayers, WsChannels,
+    list_models, ws_handler,
 };
 use webscoket_realtime_prac::models::WsMessage;
 
@@ -67,14 +70,24 @@ async fn test_model_one_time_use() {
     let pool_clone = pool.clone();
 
     let lobby_players: LobbyPlayers = Arc::new(Mutex::new(HashMap::new()));
+    let arenas: Arenas = Arc::new(ArenaRegistry::load_dir(ARENA_DATA_DIR).unwrap());
+    let replays: Replays = Arc::new(ReplayStore::new(
+        std::env::temp_dir().join(format!("test_replays_{}", Uuid::new_v4())),
+    ));
+    let app_state = AppState {
+        sessions: matching_sessions,
+        ws_channels,
+        waiting_players,
+        lobby_players,
+        game_manager,
+        db_pool: pool.clone(),
+        arenas,
+        replays,
+    };
     let srv = actix_test::start(move || {
         App::new()
             .app_data(web::Data::new(pool_clone.clone()))
-            .app_data(web::Data::new(matching_sessions.clone()))
-            .app_data(web::Data::new(ws_channels.clone()))
-            .app_data(web::Data::new(waiting_players.clone()))
-            .app_data(web::Data::new(lobby_players.clone()))
-            .app_data(web::Data::new(game_manager.clone()))
+            .app_data(web::Data::new(app_state.clone()))
             .route("/api/models", web::get().to(list_models))
             .route("/ws", web::get().to(ws_handler))
     });
@@ -0,0 +1,177 @@
+mod common;
+
+use chrono::{Duration, Utc};
+use common::test_stats;
+use std::collections::HashMap;
+use uuid::Uuid;
+use webscoket_realtime_prac::game::replay::{
+    MAX_REPLAY_EVENTS, Replay, ReplayPlayback, ReplayRecorder, ReplayStore, validate_replay_speed,
+};
+use webscoket_realtime_prac::game::state::GameStateManager;
+use webscoket_realtime_prac::models::{
+    Character, EndReason, GameOutcome, GameResult, ReplayEventKind, Vector3,
+};
+
+fn test_game() -> GameStateManager {
+    GameStateManager::new(
+        Uuid::new_v4(),
+        vec![
+            (
+                "player_a".to_string(),
+                Character::new("model_a".to_string(), test_stats(10, 10, 0)),
+            ),
+            (
+                "player_b".to_string(),
+                Character::new("model_b".to_string(), test_stats(10, 10, 0)),
+            ),
+        ],
+    )
+}
+
+fn test_result(matching_id: Uuid) -> GameResult {
+    GameResult {
+        matching_id,
+        game_id: Uuid::new_v4(),
+        outcome: GameOutcome::Win,
+        winner_id: Some("player_a".to_string()),
+        winning_team: None,
+        loser_id: Some("player_b".to_string()),
+        player_ids: vec!["player_a".to_string(), "player_b".to_string()],
+        placements: vec!["player_a".to_string(), "player_b".to_string()],
+        end_reason: EndReason::Surrender,
+        scores: HashMap::from([("player_a".to_string(), 1), ("player_b".to_string(), 0)]),
+        play_time_seconds: 3,
+        finished_at: Utc::now(),
+    }
+}
+
+fn state_update(x: f32) -> ReplayEventKind {
+    ReplayEventKind::StateUpdate {
+        player_id: "player_a".to_string(),
+        position: Vector3::new(x, 0.0, 0.0),
+        rotation: Vector3::zero(),
+    }
+}
+
+/// offset_msが100ms間隔のリプレイを作成
+fn test_replay(event_count: usize) -> Replay {
+    let game = test_game();
+    let started_at = Utc::now();
+    let mut recorder = ReplayRecorder::new(&game, started_at);
+    for i in 0..event_count {
+        recorder.record(
+            i as u64,
+            started_at + Duration::milliseconds(100 * i as i64),
+            state_update(i as f32),
+        );
+    }
+    recorder.finish(
+        test_result(game.matching_id),
+        started_at + Duration::milliseconds(100 * event_count as i64),
+    )
+}
+
+#[test]
+fn test_recorder_records_offsets_and_result() {
+    let game = test_game();
+    let started_at = Utc::now();
+    let mut recorder = ReplayRecorder::new(&game, started_at);
+    recorder.record(
+        1,
+        started_at + Duration::milliseconds(50),
+        state_update(1.0),
+    );
+    recorder.record(
+        2,
+        started_at + Duration::milliseconds(120),
+        state_update(2.0),
+    );
+
+    let replay = recorder.finish(
+        test_result(game.matching_id),
+        started_at + Duration::milliseconds(3000),
+    );
+
+    assert_eq!(replay.matching_id, game.matching_id);
+    assert_eq!(replay.game_id, game.game_id);
+    assert_eq!(replay.player_ids, game.player_ids);
+    assert_eq!(replay.duration_ms, 3000);
+    assert!(replay.finished_at.is_some());
+    assert!(!replay.truncated);
+    let offsets: Vec<i64> = replay.events.iter().map(|event| event.offset_ms).collect();
+    assert_eq!(offsets, vec![50, 120]);
+    assert_eq!(replay.events[1].tick, 2);
+    assert_eq!(
+        replay.result.unwrap().winner_id,
+        Some("player_a".to_string())
+    );
+}
+
+#[test]
+fn test_recorder_truncates_after_event_limit() {
+    let game = test_game();
+    let now = Utc::now();
+    let mut recorder = ReplayRecorder::new(&game, now);
+    for i in 0..MAX_REPLAY_EVENTS + 10 {
+        recorder.record(i as u64, now, state_update(0.0));
+    }
+
+    let replay = recorder.finish(test_result(game.matching_id), now);
+    assert_eq!(replay.events.len(), MAX_REPLAY_EVENTS);
+    assert!(replay.truncated);
+}
+
+#[test]
+fn test_playback_emits_events_by_speed() {
+    // 0, 100, 200, 300, 400ms のイベント
+    let mut playback = ReplayPlayback::new(test_replay(5), 1.0);
+
+    assert_eq!(playback.advance(0.0).len(), 1);
+    assert_eq!(playback.advance(150.0).len(), 1);
+    assert!(!playback.is_finished());
+
+    // 2倍速では実時間100msで200ms分進む（再生位置350ms）
+    playback.set_speed(2.0);
+    let ticks: Vec<u64> = playback
+        .advance(100.0)
+        .iter()
+        .map(|event| event.tick)
+        .collect();
+    assert_eq!(ticks, vec![2, 3]);
+    assert!(!playback.is_finished());
+
+    assert_eq!(playback.advance(25.0).len(), 1);
+    assert!(playback.is_finished());
+    assert!(playback.advance(1000.0).is_empty());
+    assert!(playback.result().is_some());
+}
+
+#[test]
+fn test_validate_replay_speed() {
+    assert_eq!(validate_replay_speed(1.0), Ok(1.0));
+    assert_eq!(validate_replay_speed(8.0), Ok(8.0));
+    assert!(validate_replay_speed(0.0).is_err());
+    assert!(validate_replay_speed(-1.0).is_err());
+    assert!(validate_replay_speed(16.0).is_err());
+    assert!(validate_replay_speed(f32::NAN).is_err());
+}
+
+#[actix_rt::test]
+async fn test_store_save_and_load() {
+    let dir = std::env::temp_dir().join(format!("test_replays_{}", Uuid::new_v4()));
+    let store = ReplayStore::new(&dir);
+    let replay = test_replay(3);
+
+    let path = store.save(&replay).await.unwrap();
+    assert_eq!(path, store.path(&replay.game_id));
+
+    let loaded = store.load(&replay.game_id).await.unwrap().unwrap();
+    assert_eq!(loaded.game_id, replay.game_id);
+    assert_eq!(loaded.events.len(), 3);
+    assert_eq!(loaded.duration_ms, replay.duration_ms);
+
+    // 保存されていないリプレイはNone
+    assert!(store.load(&Uuid::new_v4()).await.unwrap().is_none());
+
+    let _ = std::fs::remove_dir_all(dir);
+}
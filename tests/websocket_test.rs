@@ -1,4 +1,4 @@
-use actix::Actor;
+use actix::{Actor, Addr};
 use actix_test;
 use actix_web::{App, web};
 use chrono::Utc;
@@ -10,9 +10,15 @@ use std::sync::{Arc, Mutex};
 use tokio::time::{Duration, timeout};
 use tokio_tungstenite::{connect_async, tungstenite::Message};
 use uuid::Uuid;
+use webscoket_realtime_prac::game::arena::{ARENA_DATA_DIR, ArenaRegistry};
 use webscoket_realtime_prac::game::manager::GameManager;
-use webscoket_realtime_prac::handlers::{MatchingSessions, WaitingPlayers, WsChannels, ws_handler};
-use webscoket_realtime_prac::models::{MatchingSession, MatchingStatus, Player, WsMessage};
+use webscoket_realtime_prac::game::replay::ReplayStore;
+use webscoket_realtime_prac::handlers::{
+    AppState, MatchingSessions, WaitingPlayers, WsChannels, ws_handler,
+};
+use webscoket_realtime_prac::models::{
+    MatchSettings, MatchingSession, MatchingStatus, Player, WsMessage,
+};
 
 async fn create_test_db_pool() -> SqlitePool {
     let pool = SqlitePoolOptions::new()
@@ -29,6 +35,28 @@ async fn create_test_db_pool() -> SqlitePool {
     pool
 }
 
+/// ws_handlerに渡す共有状態（ロビー・アリーナ・リプレイはテストごとに新規作成）
+fn test_app_state(
+    db_pool: SqlitePool,
+    sessions: MatchingSessions,
+    ws_channels: WsChannels,
+    waiting_players: WaitingPlayers,
+    game_manager: Addr<GameManager>,
+) -> AppState {
+    AppState {
+        sessions,
+        ws_channels,
+        waiting_players,
+        lobby_players: Arc::new(Mutex::new(HashMap::new())),
+        game_manager,
+        db_pool,
+        arenas: Arc::new(ArenaRegistry::load_dir(ARENA_DATA_DIR).unwrap()),
+        replays: Arc::new(ReplayStore::new(
+            std::env::temp_dir().join(format!("test_replays_{}", Uuid::new_v4())),
+        )),
+    }
+}
+
 #[actix_rt::test]
 async fn test_websocket_connection() {
     let db_pool = create_test_db_pool().await;
@@ -37,13 +65,16 @@ async fn test_websocket_connection() {
     let waiting_players: WaitingPlayers = Arc::new(Mutex::new(HashMap::new()));
     let game_manager = GameManager::new(matching_sessions.clone()).start();
 
+    let app_state = test_app_state(
+        db_pool,
+        matching_sessions,
+        ws_channels,
+        waiting_players,
+        game_manager,
+    );
     let srv = actix_test::start(move || {
         App::new()
-            .app_data(web::Data::new(db_pool.clone()))
-            .app_data(web::Data::new(matching_sessions.clone()))
-            .app_data(web::Data::new(ws_channels.clone()))
-            .app_data(web::Data::new(waiting_players.clone()))
-            .app_data(web::Data::new(game_manager.clone()))
+            .app_data(web::Data::new(app_state.clone()))
             .route("/ws", web::get().to(ws_handler))
     });
 
@@ -92,13 +123,16 @@ async fn test_ready_with_model_message() {
     let waiting_players: WaitingPlayers = Arc::new(Mutex::new(HashMap::new()));
     let game_manager = GameManager::new(matching_sessions.clone()).start();
 
+    let app_state = test_app_state(
+        db_pool,
+        matching_sessions,
+        ws_channels,
+        waiting_players,
+        game_manager,
+    );
     let srv = actix_test::start(move || {
         App::new()
-            .app_data(web::Data::new(db_pool.clone()))
-            .app_data(web::Data::new(matching_sessions.clone()))
-            .app_data(web::Data::new(ws_channels.clone()))
-            .app_data(web::Data::new(waiting_players.clone()))
-            .app_data(web::Data::new(game_manager.clone()))
+            .app_data(web::Data::new(app_state.clone()))
             .route("/ws", web::get().to(ws_handler))
     });
 
@@ -138,13 +172,16 @@ async fn test_input_move_message() {
     let waiting_players: WaitingPlayers = Arc::new(Mutex::new(HashMap::new()));
     let game_manager = GameManager::new(matching_sessions.clone()).start();
 
+    let app_state = test_app_state(
+        db_pool,
+        matching_sessions,
+        ws_channels,
+        waiting_players,
+        game_manager,
+    );
     let srv = actix_test::start(move || {
         App::new()
-            .app_data(web::Data::new(db_pool.clone()))
-            .app_data(web::Data::new(matching_sessions.clone()))
-            .app_data(web::Data::new(ws_channels.clone()))
-            .app_data(web::Data::new(waiting_players.clone()))
-            .app_data(web::Data::new(game_manager.clone()))
+            .app_data(web::Data::new(app_state.clone()))
             .route("/ws", web::get().to(ws_handler))
     });
 
@@ -211,26 +248,34 @@ async fn test_opponent_gets_character_selection_message() {
     let session = MatchingSession {
         matching_id,
         creator_username: None,
-        player_a: Player::new(player_a_id.clone()),
-        player_b: Some(Player::new(player_b_id.clone())),
+        players: vec![
+            Player::new(player_a_id.clone()),
+            Player::new(player_b_id.clone()),
+        ],
         status: MatchingStatus::Waiting,
         created_at: Utc::now(),
         last_active_at: None,
         is_battle_started: false,
         is_battle_finished: false,
+        settings: MatchSettings::default(),
+        rematch_deadline: None,
+        rematch_requested_by: None,
     };
     matching_sessions
         .lock()
         .unwrap()
         .insert(matching_id, session);
 
+    let app_state = test_app_state(
+        db_pool,
+        matching_sessions,
+        ws_channels,
+        waiting_players,
+        game_manager,
+    );
     let srv = actix_test::start(move || {
         App::new()
-            .app_data(web::Data::new(db_pool.clone()))
-            .app_data(web::Data::new(matching_sessions.clone()))
-            .app_data(web::Data::new(ws_channels.clone()))
-            .app_data(web::Data::new(waiting_players.clone()))
-            .app_data(web::Data::new(game_manager.clone()))
+            .app_data(web::Data::new(app_state.clone()))
             .route("/ws", web::get().to(ws_handler))
     });
 